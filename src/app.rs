@@ -0,0 +1,397 @@
+use crate::picker::{self, Pick};
+use crate::progress::{ExportTasks, Progress};
+use log::{error, info, warn};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tmo_pdf_downloader::Client;
+use tmo_pdf_downloader::errors::{CacheError, ChapterError, FetchError, LibraryError};
+use tmo_pdf_downloader::export::chapter::ChapterExport;
+use tmo_pdf_downloader::i18n::Msg;
+use tmo_pdf_downloader::models::serie::{Chapter, Provider, Serie, SerieUrlInfo};
+use tmo_pdf_downloader::sites::{SiteAdapter, SiteRegistry, SiteUrl};
+use url::Url;
+
+/// Failure of a command, reported once by `main`.
+#[derive(Error, Debug)]
+pub enum RunError {
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+
+    #[error(transparent)]
+    Library(#[from] LibraryError),
+
+    #[error(transparent)]
+    Chapter(#[from] ChapterError),
+
+    #[error("Could not use the terminal: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("{}", Msg::DownloadsFailed(*.0))]
+    Failed(usize),
+}
+
+/// What the user asked to download.
+pub enum Target {
+    Id(u32),
+    Serie(SerieUrlInfo),
+    // The last released chapter of the serie.
+    Latest(SerieUrlInfo),
+    Reader(Arc<dyn SiteAdapter>, String),
+}
+
+/// Settings of the command line shared by every command.
+pub struct App {
+    pub sites: SiteRegistry,
+    pub progress: Progress,
+    pub export: ChapterExport,
+    /// Folder the series are cached in.
+    pub cache_folder: PathBuf,
+    pub no_cache: bool,
+    /// Times a page is downloaded again when it is incomplete or corrupted.
+    pub retries: u32,
+    pub interactive: bool,
+    /// Output path given by the user, chapters are named after themselves without it.
+    pub output: Option<String>,
+}
+
+impl App {
+    pub fn client_for(&self, site: Arc<dyn SiteAdapter>) -> Client {
+        return Client::new().with_retries(self.retries).with_site(site);
+    }
+
+    // Reads the serie from cache, or fetches it when there is an url.
+    pub fn load_serie(
+        &self,
+        index: u32,
+        url_info: Option<SerieUrlInfo>,
+    ) -> Result<Serie, FetchError> {
+        let cached = if self.no_cache {
+            Err(CacheError::CacheNotFound)
+        } else {
+            Serie::from_cache(&self.cache_folder, &index.to_string())
+        };
+
+        let url_info = match cached {
+            Ok(mut serie) if !serie.url_info.kind.is_empty() => {
+                // The site may have moved since, see `--base-domain`.
+                if let Some(site) = self.sites.by_name(&serie.url_info.site) {
+                    serie.rehost(site.as_ref());
+                }
+                return Ok(serie);
+            }
+            // Caches written before the type was stored do not tell the reading
+            // direction, the serie is fetched again from its cached url.
+            Ok(serie) => {
+                info!(index; "cached serie has no type, fetching it again");
+                url_info.or_else(|| match self.sites.parse_url(&serie.url_info.url) {
+                    Ok((_, SiteUrl::Serie(url_info))) => Some(url_info),
+                    _ => None,
+                })
+            }
+            Err(error) => {
+                info!(index, reason:% = error; "serie not cached, fetching it");
+                url_info
+            }
+        };
+
+        let part = match url_info {
+            Some(part) => part,
+            None => self.resolve_id(index)?,
+        };
+        return self.fetch_serie(part);
+    }
+
+    // Fetches the serie from its site and refreshes the cache.
+    pub fn fetch_serie(&self, mut url_info: SerieUrlInfo) -> Result<Serie, FetchError> {
+        let index = url_info.index;
+        let site = self
+            .sites
+            .by_name(&url_info.site)
+            .ok_or_else(|| FetchError::UnknownSite(url_info.site.clone()))?;
+        // Urls from the cache or the library may point to a previous domain.
+        url_info.rehost(site.as_ref());
+        let task = self.progress.spinner(&Msg::SerieStep.to_string());
+        let manga = Serie::fetch(&self.client_for(site), url_info)?;
+        task.finish(&Msg::ChaptersFound(manga.chapters.len()).to_string());
+
+        if !self.no_cache
+            && let Err(error) = manga.to_cache(&self.cache_folder, &index.to_string())
+        {
+            warn!(index, error:%; "could not write the serie to cache");
+        }
+
+        return Ok(manga);
+    }
+
+    // Asks every site for the url of the serie with the given id.
+    fn resolve_id(&self, index: u32) -> Result<SerieUrlInfo, FetchError> {
+        let task = self.progress.spinner(&Msg::SerieStep.to_string());
+        let mut last_error = FetchError::UnknownId(index);
+        for site in self.sites.iter() {
+            match self.client_for(site.clone()).resolve_id(index) {
+                Ok(url_info) => {
+                    task.finish(&url_info.url);
+                    return Ok(url_info);
+                }
+                Err(error) => {
+                    info!(index, site = site.name(), error:%; "id not resolved");
+                    last_error = error;
+                }
+            }
+        }
+        return Err(last_error);
+    }
+
+    // Finds the serie and chapter of a reader url. When the serie can not be
+    // resolved, or the chapter is not in it, the chapter is added on its own.
+    fn resolve_reader(
+        &self,
+        site: Arc<dyn SiteAdapter>,
+        reader_url: &str,
+    ) -> Result<(Serie, Pick), FetchError> {
+        let client = self.client_for(site.clone());
+        let html = client.fetch(reader_url)?;
+        let page_url = Url::parse(reader_url).expect("Reader urls come from a parsed url.");
+        let reader = site.parse_reader(&page_url, &html);
+        info!(url = reader_url, serie:? = reader.serie_url, chapter:? = reader.chapter_name; "reader resolved");
+
+        let parent = match reader
+            .serie_url
+            .as_deref()
+            .map(|url| self.sites.parse_url(url))
+        {
+            Some(Ok((_, SiteUrl::Serie(url_info)))) => self
+                .load_serie(url_info.index, Some(url_info))
+                .inspect_err(|error| warn!(url = reader_url, error:%; "could not load the serie"))
+                .ok(),
+            _ => None,
+        };
+
+        let mut manga = match parent {
+            Some(manga) => manga,
+            None => {
+                warn!(url = reader_url; "the serie of the chapter could not be resolved");
+                let name = reader
+                    .chapter_name
+                    .clone()
+                    .unwrap_or(reader_url.to_string());
+                let url_info = SerieUrlInfo::new(
+                    reader_url.to_string(),
+                    reader_index(reader_url),
+                    String::new(),
+                    false,
+                    String::new(),
+                    site.name().to_string(),
+                );
+                Serie::new(url_info, name, None, Vec::new())
+            }
+        };
+
+        let same_reader = |link: &str| {
+            return Url::parse(link).is_ok_and(|link| site.same_reader(&link, &page_url));
+        };
+        let found = manga.chapters.iter().position(|chapter| {
+            chapter.providers.iter().any(|p| same_reader(&p.link))
+                || reader.chapter_number == Some(chapter.number)
+        });
+
+        let index = match found {
+            Some(index) => index,
+            None => {
+                let name = reader.chapter_name.unwrap_or(manga.title.clone());
+                let chapter =
+                    Chapter::new(reader.chapter_number.unwrap_or((0, 0)), name, Vec::new());
+                manga.chapters.push(chapter);
+                manga.chapters.len() - 1
+            }
+        };
+
+        // The pasted url is the scan the user wants. The serie page lists its
+        // uploads under other links, they are matched by the scan name or by
+        // where they redirect, and the url is added only when none matches.
+        let providers = &mut manga.chapters[index].providers;
+        let same_scan = |p: &Provider| {
+            return reader
+                .scan
+                .as_ref()
+                .is_some_and(|scan| p.scan.to_lowercase() == scan.to_lowercase());
+        };
+        let redirects_here = |p: &Provider| match client.final_url(&p.link) {
+            Ok(link) => same_reader(&link),
+            Err(error) => {
+                warn!(url = p.link.as_str(), error:%; "could not follow the link of the scan");
+                false
+            }
+        };
+        let position = providers
+            .iter()
+            .position(|p| same_reader(&p.link))
+            .or_else(|| providers.iter().position(same_scan))
+            .or_else(|| providers.iter().position(redirects_here));
+        let provider = match position {
+            Some(provider) => provider,
+            None => {
+                let scan = reader.scan.clone().unwrap_or_default();
+                providers.push(Provider::new(scan, reader_url.to_string()));
+                providers.len() - 1
+            }
+        };
+
+        // The pages are read from the reader already fetched.
+        let image_urls = site
+            .parse_chapter(&html)
+            .inspect_err(|error| warn!(url = reader_url, error:%; "could not read the pages"))
+            .ok();
+
+        return Ok((
+            manga,
+            Pick {
+                chapter: index,
+                provider,
+                image_urls,
+            },
+        ));
+    }
+
+    // Loads the serie of the target and the chapter and provider it points to.
+    pub fn load_target(&self, target: Target) -> Result<(Serie, Pick), FetchError> {
+        let first = Pick {
+            chapter: 0,
+            provider: 0,
+            image_urls: None,
+        };
+        return match target {
+            Target::Id(index) => Ok((self.load_serie(index, None)?, first)),
+            Target::Serie(url_info) => {
+                Ok((self.load_serie(url_info.index, Some(url_info))?, first))
+            }
+            Target::Latest(url_info) => {
+                let manga = self.load_serie(url_info.index, Some(url_info))?;
+                // Chapters are stored oldest first.
+                let latest = Pick {
+                    chapter: manga.chapters.len().saturating_sub(1),
+                    provider: 0,
+                    image_urls: None,
+                };
+                Ok((manga, latest))
+            }
+            Target::Reader(site, url) => self.resolve_reader(site, &url),
+        };
+    }
+
+    // Downloads the chapters of the target and writes the output. A chapter that
+    // fails is reported and the next one is downloaded anyway.
+    pub fn download(&self, target: Target) -> Result<(), RunError> {
+        let (mut manga, target_pick) = self.load_target(target)?;
+        let site = self
+            .sites
+            .by_name(&manga.url_info.site)
+            .ok_or_else(|| FetchError::UnknownSite(manga.url_info.site.clone()))?;
+        let client = self.client_for(site);
+
+        let mut picks = if self.interactive {
+            if !io::stdin().is_terminal() {
+                error!("the interactive mode needs a terminal");
+                std::process::exit(1);
+            }
+            let folder = self
+                .export
+                .cache_folder
+                .join(manga.url_info.index.to_string());
+            picker::pick(&manga, &folder)?
+        } else {
+            vec![target_pick.clone()]
+        };
+        if picks.is_empty() {
+            info!("no chapter picked");
+            return Ok(());
+        }
+
+        // The picked provider goes first, it is the one every step reads from.
+        // The pages of a pasted reader url were already read with it.
+        for pick in &mut picks {
+            prefer_provider(&mut manga.chapters[pick.chapter], pick.provider);
+            if pick.chapter == target_pick.chapter && pick.provider == target_pick.provider {
+                pick.image_urls = target_pick.image_urls.clone();
+            }
+        }
+
+        let mut failed: usize = 0;
+        let mut chapters_task = self
+            .progress
+            .bar(&Msg::ChaptersStep.to_string(), picks.len() as u64);
+        for pick in &picks {
+            let name = &manga.chapters[pick.chapter].name;
+            let output = match &self.output {
+                Some(output) if picks.len() > 1 => format!("{} - {}", output, name),
+                Some(output) => output.clone(),
+                None => name.clone(),
+            };
+            let result =
+                self.download_chapter(&self.export, &client, &manga, pick, Path::new(&output));
+            if let Err(error) = result {
+                error!(chapter = name.as_str(), error:%; "could not download the chapter");
+                failed += 1;
+            }
+            chapters_task.inc(0);
+        }
+        chapters_task.finish(&Msg::ChaptersDone.to_string());
+
+        if failed > 0 {
+            return Err(RunError::Failed(failed));
+        }
+        return Ok(());
+    }
+
+    // Downloads one chapter of the serie and writes it to `output` with `export`.
+    pub fn download_chapter(
+        &self,
+        export: &ChapterExport,
+        client: &Client,
+        manga: &Serie,
+        pick: &Pick,
+        output: &Path,
+    ) -> Result<(), ChapterError> {
+        let chapter = &manga.chapters[pick.chapter];
+        info!(index = pick.chapter, name = chapter.name.as_str(); "chapter selected");
+        let task = self.progress.spinner(&Msg::ReaderStep.to_string());
+        let urls = match &pick.image_urls {
+            Some(urls) => urls.clone(),
+            None => chapter.image_urls(client)?,
+        };
+        task.finish(&Msg::PagesFound(urls.len()).to_string());
+
+        let mut tasks = ExportTasks::new(&self.progress);
+        let warnings = export.run(client, manga, chapter, &urls, output, &mut |event| {
+            tasks.report(event)
+        })?;
+        for warning in warnings {
+            warn!(page = warning.page, url = warning.source.as_str(); "{}", warning.message);
+        }
+
+        let scan = chapter.providers.first().map(|p| p.scan.as_str());
+        picker::mark_downloaded(
+            &export.chapter_folder(manga, chapter),
+            scan.unwrap_or_default(),
+        )?;
+        return Ok(());
+    }
+}
+
+// Index given to a chapter whose serie is unknown, so its pages get their own
+// cache folder. The high bit keeps it apart from the ids of the site.
+fn reader_index(reader_url: &str) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    reader_url.hash(&mut hasher);
+    return (hasher.finish() as u32) | 0x8000_0000;
+}
+
+// Moves the provider to the front, every step reads the chapter from the first one.
+pub fn prefer_provider(chapter: &mut Chapter, provider: usize) {
+    if provider > 0 {
+        chapter.providers.swap(0, provider);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::client::{Retry, check_body, check_headers, header_map};
+use crate::errors::{DownloadError, FetchError, HttpError};
+use crate::models::search::SearchResult;
+use crate::models::serie::SerieUrlInfo;
+use crate::processing::Page;
+use crate::processing::convert::image_extension;
+use crate::sites::tmo::Tmo;
+use crate::sites::{SiteAdapter, match_id};
+use log::debug;
+use reqwest::Response;
+use reqwest::header::{HeaderMap, HeaderValue, REFERER};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Non blocking version of `Client`, for programs already running on tokio.
+#[derive(Debug, Clone)]
+pub struct AsyncClient {
+    http: reqwest::Client,
+    retries: u32,
+    decode_check: bool,
+    site: Arc<dyn SiteAdapter>,
+}
+
+impl Default for AsyncClient {
+    fn default() -> Self {
+        return AsyncClient::new();
+    }
+}
+
+impl AsyncClient {
+    pub fn new() -> AsyncClient {
+        let http = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .expect("Client configuration is hardcoded.");
+        return AsyncClient {
+            http,
+            retries: 3,
+            decode_check: false,
+            site: Arc::new(Tmo::default()),
+        };
+    }
+
+    /// Site used to parse the pages, TMO by default.
+    pub fn with_site(mut self, site: Arc<dyn SiteAdapter>) -> AsyncClient {
+        self.site = site;
+        return self;
+    }
+
+    pub fn site(&self) -> &dyn SiteAdapter {
+        return self.site.as_ref();
+    }
+
+    /// Times an image is downloaded again when it is incomplete or corrupted.
+    pub fn with_retries(mut self, retries: u32) -> AsyncClient {
+        self.retries = retries;
+        return self;
+    }
+
+    /// Decodes every image to check it is not corrupted. Off by default, the
+    /// decode runs on the executor thread and blocks it for a while. Without it
+    /// the headers, length and format of the images are still checked.
+    pub fn with_decode_check(mut self, decode_check: bool) -> AsyncClient {
+        self.decode_check = decode_check;
+        return self;
+    }
+
+    /// Async version of `Client::fetch`.
+    pub async fn fetch(&self, url: &str) -> Result<String, HttpError> {
+        let response = self.send(url, HeaderMap::new()).await?;
+        return response.text().await.map_err(HttpError::new);
+    }
+
+    /// Async version of `Client::final_url`.
+    pub async fn final_url(&self, url: &str) -> Result<String, HttpError> {
+        return Ok(self.send(url, HeaderMap::new()).await?.url().to_string());
+    }
+
+    async fn send(&self, url: &str, mut headers_map: HeaderMap) -> Result<Response, HttpError> {
+        if !headers_map.contains_key(REFERER) {
+            headers_map.insert(REFERER, HeaderValue::from_static(""));
+        }
+        debug!(url; "fetching");
+        let response = self
+            .http
+            .get(url)
+            .headers(headers_map)
+            .send()
+            .await
+            .map_err(HttpError::new)?;
+        debug!(url, status = response.status().as_u16(); "response received");
+        return response.error_for_status().map_err(HttpError::new);
+    }
+
+    /// Async version of `Client::search`.
+    pub async fn search(&self, query: &str) -> Result<Vec<SearchResult>, FetchError> {
+        let Some(url) = self.site.search_url(query) else {
+            return Ok(Vec::new());
+        };
+        let html = self.fetch(&url).await?;
+        let results = self.site.parse_search(&html);
+        debug!(query, results = results.len(); "search done");
+        return Ok(results);
+    }
+
+    /// Async version of `Client::resolve_id`.
+    pub async fn resolve_id(&self, index: u32) -> Result<SerieUrlInfo, FetchError> {
+        for url in self.site.id_urls(index) {
+            let response = match self.send(&url, HeaderMap::new()).await {
+                Ok(response) => response,
+                // The id does not exist with this type, the next one is tried.
+                Err(error) if error.is_client_error() => {
+                    debug!(url, error:%; "id not found");
+                    continue;
+                }
+                Err(error) => return Err(error.into()),
+            };
+            let final_url = response.url().clone();
+            let html = response.text().await.map_err(HttpError::new)?;
+            if let Some(url_info) = match_id(self.site(), index, &final_url, &html) {
+                debug!(index, url = url_info.url.as_str(); "id resolved");
+                return Ok(url_info);
+            }
+            // A redirect already leads to the serie, the other types would too.
+            if final_url.as_str() != url {
+                break;
+            }
+        }
+        return Err(FetchError::UnknownId(index));
+    }
+
+    /// Downloads an image and checks it is complete, downloading it again up
+    /// to the configured retries when it is not.
+    pub async fn fetch_image(&self, url: &str) -> Result<Vec<u8>, DownloadError> {
+        let mut retry = Retry::new(url, self.retries);
+
+        loop {
+            let result = match self
+                .send(url, header_map(self.site.image_headers(url)))
+                .await
+            {
+                Ok(response) => validate_image(response, self.decode_check).await,
+                Err(error) => Err(DownloadError::from(error)),
+            };
+
+            match result {
+                Ok(bytes) => return Ok(bytes),
+                Err(error) => match retry.next(&error) {
+                    Some(wait) => tokio::time::sleep(wait).await,
+                    None => return Err(error),
+                },
+            }
+        }
+    }
+
+    /// Downloads an image into `folder` as `{name}.{extension}`,
+    /// the extension follows the real content of the image.
+    pub async fn download_page(
+        &self,
+        url: &str,
+        folder: &Path,
+        name: &str,
+    ) -> Result<Page, DownloadError> {
+        let bytes = self.fetch_image(url).await?;
+        let path = folder.join(format!("{}.{}", name, image_extension(&bytes)));
+        tokio::fs::write(&path, &bytes).await?;
+        debug!(url, path:% = path.display(), bytes = bytes.len(); "page saved");
+        return Ok(Page::new(path, url.to_string()));
+    }
+}
+
+async fn validate_image(response: Response, decode: bool) -> Result<Vec<u8>, DownloadError> {
+    let expected = check_headers(response.headers())?;
+    let bytes = response.bytes().await.map_err(HttpError::new)?.to_vec();
+    return check_body(bytes, expected, decode);
+}
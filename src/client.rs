@@ -0,0 +1,345 @@
+use crate::errors::{DownloadError, FetchError, HttpError};
+use crate::models::search::SearchResult;
+use crate::models::serie::SerieUrlInfo;
+use crate::processing::Page;
+use crate::processing::convert::image_extension;
+use crate::sites::tmo::Tmo;
+use crate::sites::{SiteAdapter, match_id};
+use log::{debug, warn};
+use reqwest::blocking::Response;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, REFERER};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// HTTP client used for every request to the site and its image servers.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::blocking::Client,
+    retries: u32,
+    site: Arc<dyn SiteAdapter>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        return Client::new();
+    }
+}
+
+impl Client {
+    pub fn new() -> Client {
+        let http = reqwest::blocking::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .expect("Client configuration is hardcoded.");
+        return Client {
+            http,
+            retries: 3,
+            site: Arc::new(Tmo::default()),
+        };
+    }
+
+    /// Site used to parse the pages, TMO by default.
+    pub fn with_site(mut self, site: Arc<dyn SiteAdapter>) -> Client {
+        self.site = site;
+        return self;
+    }
+
+    pub fn site(&self) -> &dyn SiteAdapter {
+        return self.site.as_ref();
+    }
+
+    /// Times an image is downloaded again when it is incomplete or corrupted.
+    pub fn with_retries(mut self, retries: u32) -> Client {
+        self.retries = retries;
+        return self;
+    }
+
+    /// Downloads a page and returns its body.
+    pub fn fetch(&self, url: &str) -> Result<String, HttpError> {
+        return self
+            .send(url, HeaderMap::new())?
+            .text()
+            .map_err(HttpError::new);
+    }
+
+    /// Url a page ends at after following its redirects.
+    pub fn final_url(&self, url: &str) -> Result<String, HttpError> {
+        return Ok(self.send(url, HeaderMap::new())?.url().to_string());
+    }
+
+    fn send(&self, url: &str, mut headers_map: HeaderMap) -> Result<Response, HttpError> {
+        if !headers_map.contains_key(REFERER) {
+            headers_map.insert(REFERER, HeaderValue::from_static(""));
+        }
+        debug!(url; "fetching");
+        let response = self
+            .http
+            .get(url)
+            .headers(headers_map)
+            .send()
+            .map_err(HttpError::new)?;
+        debug!(url, status = response.status().as_u16(); "response received");
+        return response.error_for_status().map_err(HttpError::new);
+    }
+
+    /// Searches the site, returns nothing when the site has no search.
+    pub fn search(&self, query: &str) -> Result<Vec<SearchResult>, FetchError> {
+        let Some(url) = self.site.search_url(query) else {
+            return Ok(Vec::new());
+        };
+        let html = self.fetch(&url)?;
+        let results = self.site.parse_search(&html);
+        debug!(query, results = results.len(); "search done");
+        return Ok(results);
+    }
+
+    /// Discovers the url of a serie knowing only its id, stopping at the first
+    /// url that redirects. Any error other than a refused url is returned.
+    pub fn resolve_id(&self, index: u32) -> Result<SerieUrlInfo, FetchError> {
+        for url in self.site.id_urls(index) {
+            let response = match self.send(&url, HeaderMap::new()) {
+                Ok(response) => response,
+                // The id does not exist with this type, the next one is tried.
+                Err(error) if error.is_client_error() => {
+                    debug!(url, error:%; "id not found");
+                    continue;
+                }
+                Err(error) => return Err(error.into()),
+            };
+            let final_url = response.url().clone();
+            let html = response.text().map_err(HttpError::new)?;
+            if let Some(url_info) = match_id(self.site(), index, &final_url, &html) {
+                debug!(index, url = url_info.url.as_str(); "id resolved");
+                return Ok(url_info);
+            }
+            // A redirect already leads to the serie, the other types would too.
+            if final_url.as_str() != url {
+                break;
+            }
+        }
+        return Err(FetchError::UnknownId(index));
+    }
+
+    /// Downloads an image and checks it is complete and decodable,
+    /// downloading it again up to the configured retries when it is not.
+    pub fn fetch_image(&self, url: &str) -> Result<Vec<u8>, DownloadError> {
+        let mut retry = Retry::new(url, self.retries);
+
+        loop {
+            let result = self
+                .send(url, header_map(self.site.image_headers(url)))
+                .map_err(DownloadError::from)
+                .and_then(validate_image);
+
+            match result {
+                Ok(bytes) => return Ok(bytes),
+                Err(error) => match retry.next(&error) {
+                    Some(wait) => thread::sleep(wait),
+                    None => return Err(error),
+                },
+            }
+        }
+    }
+
+    /// Downloads an image into `folder` as `{name}.{extension}`,
+    /// the extension follows the real content of the image.
+    pub fn download_page(
+        &self,
+        url: &str,
+        folder: &Path,
+        name: &str,
+    ) -> Result<Page, DownloadError> {
+        let bytes = self.fetch_image(url)?;
+        let path = folder.join(format!("{}.{}", name, image_extension(&bytes)));
+        fs::write(&path, &bytes)?;
+        debug!(url, path:% = path.display(), bytes = bytes.len(); "page saved");
+        return Ok(Page::new(path, url.to_string()));
+    }
+}
+
+fn validate_image(response: Response) -> Result<Vec<u8>, DownloadError> {
+    let expected = check_headers(response.headers())?;
+    let bytes = response.bytes().map_err(HttpError::new)?.to_vec();
+    return check_body(bytes, expected, true);
+}
+
+/// Attempts left to download an image, shared by the blocking and the async client.
+pub(crate) struct Retry<'a> {
+    url: &'a str,
+    attempt: u32,
+    retries: u32,
+}
+
+impl Retry<'_> {
+    pub(crate) fn new(url: &str, retries: u32) -> Retry<'_> {
+        return Retry {
+            url,
+            attempt: 0,
+            retries,
+        };
+    }
+
+    /// Time to wait before downloading again, `None` when the error is final.
+    pub(crate) fn next(&mut self, error: &DownloadError) -> Option<Duration> {
+        if self.attempt >= self.retries || !is_transient(error) {
+            return None;
+        }
+        self.attempt += 1;
+        warn!(url = self.url, attempt = self.attempt, retries = self.retries, error:%; "invalid image, retrying");
+        return Some(Duration::from_secs(self.attempt as u64));
+    }
+}
+
+/// Headers given by a site, the invalid ones are skipped.
+pub(crate) fn header_map(headers: Vec<(String, String)>) -> HeaderMap {
+    return headers
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let name = HeaderName::from_bytes(name.as_bytes()).ok()?;
+            let value = HeaderValue::from_str(&value).ok()?;
+            return Some((name, value));
+        })
+        .collect();
+}
+
+/// Whether downloading the image again may work. Bodies that arrive broken are
+/// retried, permanent answers like 404 or 403 are not.
+pub(crate) fn is_transient(error: &DownloadError) -> bool {
+    return match error {
+        DownloadError::Request(error) => error.is_transient(),
+        DownloadError::InvalidContentType(_)
+        | DownloadError::Truncated { .. }
+        | DownloadError::UnknownFormat
+        | DownloadError::Corrupted(_) => true,
+        _ => false,
+    };
+}
+
+/// Checks the headers of an image response, returning the expected length.
+pub(crate) fn check_headers(headers: &HeaderMap) -> Result<Option<u64>, DownloadError> {
+    // Some CDNs do not send a content type or send a generic one.
+    if let Some(content_type) = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()) {
+        let is_image = content_type.starts_with("image/")
+            || content_type.starts_with("application/octet-stream")
+            || content_type.starts_with("binary/octet-stream");
+        if !is_image {
+            return Err(DownloadError::InvalidContentType(content_type.to_string()));
+        }
+    }
+
+    let expected = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    return Ok(expected);
+}
+
+/// Checks the body of an image is complete and of a known format,
+/// and with `decode` that it can be fully decoded.
+pub(crate) fn check_body(
+    bytes: Vec<u8>,
+    expected: Option<u64>,
+    decode: bool,
+) -> Result<Vec<u8>, DownloadError> {
+    if let Some(expected) = expected
+        && bytes.len() as u64 != expected
+    {
+        return Err(DownloadError::Truncated {
+            expected,
+            received: bytes.len() as u64,
+        });
+    }
+
+    if image::guess_format(&bytes).is_err() {
+        return Err(DownloadError::UnknownFormat);
+    }
+    if decode {
+        image::load_from_memory(&bytes)?;
+    }
+
+    return Ok(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    // Answers a single request with `response` and closes the connection.
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            let _ = stream.write_all(response.as_bytes());
+        });
+        return url;
+    }
+
+    fn fetch_error(response: &'static str) -> HttpError {
+        return Client::new().fetch(&serve_once(response)).unwrap_err();
+    }
+
+    #[test]
+    fn server_errors_and_rate_limits_are_transient() {
+        let error = fetch_error("HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n");
+        assert!(error.is_transient());
+        let error = fetch_error("HTTP/1.1 429 Too Many Requests\r\ncontent-length: 0\r\n\r\n");
+        assert!(error.is_transient());
+    }
+
+    #[test]
+    fn client_errors_are_not_transient() {
+        let error = fetch_error("HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\n\r\n");
+        assert!(!error.is_transient());
+        assert!(error.is_client_error());
+    }
+
+    #[test]
+    fn cut_bodies_are_transient() {
+        let error = fetch_error("HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\nonly a part");
+        assert_eq!(error.status(), None);
+        assert!(error.is_transient());
+    }
+
+    #[test]
+    fn refused_connections_are_transient() {
+        // Nothing listens on the port once the listener is dropped.
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let error = Client::new()
+            .fetch(&format!("http://{}/", address))
+            .unwrap_err();
+        assert!(error.is_transient());
+    }
+
+    #[test]
+    fn final_url_follows_redirects() {
+        let target = serve_once("HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n");
+        let response: &'static str = format!(
+            "HTTP/1.1 302 Found\r\nlocation: {}viewer/abc/paginated\r\ncontent-length: 0\r\n\r\n",
+            target
+        )
+        .leak();
+        let final_url = Client::new().final_url(&serve_once(response)).unwrap();
+        assert_eq!(final_url, format!("{}viewer/abc/paginated", target));
+    }
+
+    #[test]
+    fn broken_images_are_transient() {
+        assert!(is_transient(&DownloadError::Truncated {
+            expected: 100,
+            received: 10
+        }));
+        assert!(is_transient(&DownloadError::UnknownFormat));
+        assert!(!is_transient(&DownloadError::NoProvider));
+    }
+}
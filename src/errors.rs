@@ -1,24 +1,202 @@
+use crate::i18n::Msg;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum CacheError {
-    #[error("Could not read the cache directory: {0}")]
+    #[error("{msg}: {0}", msg = Msg::CacheIo)]
     Io(#[from] std::io::Error),
 
-    #[error("Cache for this manga does not exist or is not valid.")]
+    #[error("{}", Msg::CacheNotFound)]
     CacheNotFound,
 
-    #[error("The cache file is expired.")]
+    #[error("{}", Msg::CacheExpired)]
     CacheExpired,
 
+    #[error("{msg}: {0}", msg = Msg::CacheParse)]
+    Parse(#[from] serde_json::Error),
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum LibraryError {
+    #[error("Could not read or write the library file: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("Failed to parse JSON content: {0}")]
     Parse(#[from] serde_json::Error),
 }
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum SerieParseError {
-    #[error("Document do not contain title element.")]
+    #[error("{}", Msg::MissingTitle)]
     MissingTitle,
-    #[error("Fragment parsed do not contain scan name element.")]
+    #[error("{}", Msg::MissingScan)]
     MissingScan,
 }
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ProcessingError {
+    #[error("Could not read or write the image file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to decode or encode the image: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("Failed to parse JSON content: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("The ratio must be a positive number, got {0}.")]
+    InvalidRatio(f32),
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ExportError {
+    #[error("Could not write the output: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to write the CBZ archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Failed to read a page: {0}")]
+    Processing(#[from] ProcessingError),
+
+    #[error("Failed to decode or encode a page: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("Page {page} ({url}) could not be embedded: {error}")]
+    BrokenPage {
+        page: usize,
+        url: String,
+        error: Box<ExportError>,
+    },
+}
+
+/// Failure of a single chapter, from the download of its pages to the output.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ChapterError {
+    #[error(transparent)]
+    Download(#[from] DownloadError),
+
+    #[error(transparent)]
+    Processing(#[from] ProcessingError),
+
+    #[error(transparent)]
+    Export(#[from] ExportError),
+
+    #[error("Could not write the output: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A failed HTTP request. The HTTP library is an implementation detail.
+#[derive(Error, Debug)]
+#[error(transparent)]
+pub struct HttpError(reqwest::Error);
+
+impl HttpError {
+    pub(crate) fn new(error: reqwest::Error) -> HttpError {
+        return HttpError(error);
+    }
+
+    /// Status code, when the server answered with an error.
+    pub fn status(&self) -> Option<u16> {
+        return self.0.status().map(|status| status.as_u16());
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        return self.0.is_timeout();
+    }
+
+    /// Whether the server refused the request itself, sending it again would not help.
+    pub fn is_client_error(&self) -> bool {
+        return self
+            .status()
+            .is_some_and(|status| (400..500).contains(&status) && status != 429);
+    }
+
+    /// Whether the same request may work later: server errors, rate limits,
+    /// timeouts, failed connections and bodies cut while being read.
+    pub fn is_transient(&self) -> bool {
+        return match self.status() {
+            Some(status) => status >= 500 || status == 429,
+            // A body cut while being read is reported as a decode error.
+            None => {
+                self.is_timeout()
+                    || self.0.is_connect()
+                    || self.0.is_request()
+                    || self.0.is_body()
+                    || self.0.is_decode()
+            }
+        };
+    }
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum DownloadError {
+    #[error("The request failed: {0}")]
+    Request(#[from] HttpError),
+
+    #[error("The server answered with {0} instead of an image.")]
+    InvalidContentType(String),
+
+    #[error("The body is truncated, expected {expected} bytes but got {received}.")]
+    Truncated { expected: u64, received: u64 },
+
+    #[error("The body is not a known image format.")]
+    UnknownFormat,
+
+    #[error("The image is corrupted: {0}")]
+    Corrupted(#[from] image::ImageError),
+
+    #[error("Could not write the image: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The chapter has no providers.")]
+    NoProvider,
+
+    #[error("The reader page does not contain the list of images.")]
+    MissingImages,
+
+    #[error("The reader page lists an invalid image url: {0}")]
+    InvalidImageUrl(String),
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum FetchError {
+    #[error("The request failed: {0}")]
+    Request(#[from] HttpError),
+
+    #[error("Failed to parse the serie page: {0}")]
+    Parse(#[from] SerieParseError),
+
+    #[error("No serie was found with the id {0}.")]
+    UnknownId(u32),
+
+    #[error("No site is registered with the name {0}.")]
+    UnknownSite(String),
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum UrlError {
+    #[error("{}", Msg::InvalidValue)]
+    Invalid,
+
+    #[error("{}", Msg::UnknownHost)]
+    UnknownHost,
+
+    #[error("{}", Msg::MissingPath)]
+    MissingPath,
+
+    #[error("{}", Msg::InvalidFormat)]
+    InvalidFormat,
+
+    #[error("{}", Msg::InvalidId)]
+    InvalidId,
+}
@@ -0,0 +1,117 @@
+use super::{Metadata, missing_pages};
+use crate::errors::ExportError;
+use crate::models::serie::ReadingDirection;
+use crate::processing::Page;
+use std::fs::{self, File};
+use std::io::Write;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Writes a comic book archive with the images in order and a ComicInfo.xml,
+/// whose notes list the placeholders of missing pages.
+pub fn create_cbz(
+    file: File,
+    metadata: &Metadata,
+    pages: &[Page],
+    direction: ReadingDirection,
+) -> Result<(), ExportError> {
+    let mut zip = ZipWriter::new(file);
+    // Images are already compressed, deflating them only wastes time.
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    for (i, page) in pages.iter().enumerate() {
+        let extension = page
+            .path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("img");
+        zip.start_file(format!("{:04}.{}", i + 1, extension), stored)?;
+        zip.write_all(&fs::read(&page.path)?)?;
+    }
+
+    zip.start_file("ComicInfo.xml", SimpleFileOptions::default())?;
+    zip.write_all(comic_info(metadata, pages, direction).as_bytes())?;
+    zip.finish()?;
+    return Ok(());
+}
+
+fn comic_info(metadata: &Metadata, pages: &[Page], direction: ReadingDirection) -> String {
+    let manga = match direction {
+        ReadingDirection::Rtl => "YesAndRightToLeft",
+        ReadingDirection::Ltr => "No",
+    };
+    let missing = missing_pages(pages);
+    let notes = match missing.is_empty() {
+        true => String::new(),
+        false => format!("  <Notes>{}</Notes>\n", escape_xml(&missing.join("\n"))),
+    };
+
+    return format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n",
+            "<ComicInfo>\n",
+            "  <Title>{}</Title>\n",
+            "  <Series>{}</Series>\n",
+            "  <Number>{}</Number>\n",
+            "{}",
+            "  <ScanInformation>{}</ScanInformation>\n",
+            "  <Web>{}</Web>\n",
+            "  <PageCount>{}</PageCount>\n",
+            "  <Manga>{}</Manga>\n",
+            "</ComicInfo>\n"
+        ),
+        escape_xml(&metadata.chapter),
+        escape_xml(&metadata.series),
+        metadata.number,
+        notes,
+        escape_xml(&metadata.scan),
+        escape_xml(&metadata.url),
+        pages.len(),
+        manga
+    );
+}
+
+fn escape_xml(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::serie::{Chapter, Serie, SerieUrlInfo};
+    use std::path::PathBuf;
+
+    #[test]
+    fn comic_info_notes_the_missing_pages() {
+        let url_info = SerieUrlInfo::new(
+            "https://zonatmo.com/library/manga/1/serie".to_string(),
+            1,
+            "serie".to_string(),
+            false,
+            "manga".to_string(),
+            "tmo".to_string(),
+        );
+        let serie = Serie::new(url_info, "Serie".to_string(), None, Vec::new());
+        let chapter = Chapter::new((1, 0), "Capítulo 1.00".to_string(), Vec::new());
+        let metadata = Metadata::new(&serie, &chapter, "Scan");
+        let mut missing = Page::new(PathBuf::from("1.png"), "https://img/1.png?a&b".to_string());
+        missing.placeholder = true;
+        let pages = [
+            Page::new(PathBuf::from("0.jpg"), "https://img/0.jpg".to_string()),
+            missing,
+        ];
+
+        let xml = comic_info(&metadata, &pages, ReadingDirection::Rtl);
+        assert!(
+            xml.contains(
+                "<Notes>The image of page 2 is missing: https://img/1.png?a&amp;b</Notes>"
+            )
+        );
+        assert!(xml.contains("<PageCount>2</PageCount>"));
+        assert!(!comic_info(&metadata, &pages[..1], ReadingDirection::Rtl).contains("<Notes>"));
+    }
+}
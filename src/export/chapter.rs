@@ -0,0 +1,328 @@
+use super::Metadata;
+use super::cbz::create_cbz;
+use super::images::save_images;
+use super::pdf::{PdfOptions, PdfWarning, create_pdf};
+use crate::client::Client;
+use crate::errors::{ChapterError, DownloadError};
+use crate::models::serie::{Chapter, ReadingDirection, Serie};
+use crate::processing::convert::{ConvertOptions, convert_images};
+use crate::processing::credits::{CreditHashes, drop_credit_pages, hash_images};
+use crate::processing::profile::{DeviceProfile, PreferredContainer, apply_profile};
+use crate::processing::split::split_tall_images;
+use crate::processing::spreads::split_spreads;
+use crate::processing::stitch::stitch_images;
+use crate::processing::trim::trim_borders;
+use crate::processing::{Page, placeholder_page};
+use log::{info, warn};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// File format a chapter is written as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[non_exhaustive]
+pub enum OutputFormat {
+    Pdf,
+    Cbz,
+    /// A folder with the pages as numbered images.
+    Images,
+}
+
+/// A step of `ChapterExport::run`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportStep {
+    Download,
+    Credits,
+    Trim,
+    Spreads,
+    Split,
+    Stitch,
+    Convert,
+    Profile,
+    Output,
+}
+
+/// Progress of `ChapterExport::run`, to show it while it runs.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExportEvent {
+    /// A step started, with the number of pages it receives.
+    Started(ExportStep, usize),
+    /// A page was downloaded, with its size in bytes.
+    PageSaved(u64),
+    /// A step ended, with the number of pages it leaves.
+    Finished(ExportStep, usize),
+    /// Perceptual hash of every downloaded page, see `hash_pages`.
+    Hashes(Vec<String>),
+}
+
+/// Steps run on the pages of a chapter and how it is written. Every step
+/// is off by default and the chapter is written as a PDF.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ChapterExport {
+    /// Folder the pages of every chapter are downloaded to and processed in.
+    pub cache_folder: PathBuf,
+    /// `None` writes a PDF, or the container preferred by the profile.
+    pub format: Option<OutputFormat>,
+    /// Defaults to the one of the serie type.
+    pub direction: Option<ReadingDirection>,
+    /// Replace pages that can not be downloaded or decoded with a placeholder instead of failing.
+    pub skip_broken_pages: bool,
+    /// Report the perceptual hash of every page, to register new credit pages.
+    pub hash_pages: bool,
+    /// Credit pages with a hash known for the scan are dropped.
+    pub credits: Option<CreditHashes>,
+    /// Crop uniform borders, see `trim_borders` for the tolerance and limit.
+    pub trim: bool,
+    pub trim_tolerance: u8,
+    pub trim_limit: f32,
+    pub split_spreads: bool,
+    /// Split images taller than this ratio of height to width.
+    pub split_tall: Option<f32>,
+    /// Stitch consecutive images into strips up to this height.
+    pub stitch: Option<u32>,
+    /// Runs when it sets a format or a maximum width. The profile uses it too.
+    pub convert: ConvertOptions,
+    /// Prepend the cover of the serie.
+    pub cover: bool,
+    pub profile: Option<DeviceProfile>,
+    pub pdf: PdfOptions,
+}
+
+impl ChapterExport {
+    pub fn new(cache_folder: PathBuf) -> ChapterExport {
+        return ChapterExport {
+            cache_folder,
+            format: None,
+            direction: None,
+            skip_broken_pages: false,
+            hash_pages: false,
+            credits: None,
+            trim: false,
+            trim_tolerance: 16,
+            trim_limit: 0.15,
+            split_spreads: false,
+            split_tall: None,
+            stitch: None,
+            convert: ConvertOptions::default(),
+            cover: false,
+            profile: None,
+            pdf: PdfOptions::default(),
+        };
+    }
+
+    /// Folder the pages of the chapter are downloaded to.
+    pub fn chapter_folder(&self, serie: &Serie, chapter: &Chapter) -> PathBuf {
+        return self
+            .cache_folder
+            .join(serie.url_info.index.to_string())
+            .join(&chapter.name);
+    }
+
+    /// Downloads the images at `urls`, runs the steps on them and writes the
+    /// chapter to `output`, which gets the extension of the format. Returns
+    /// what was changed or lost while writing the pages.
+    pub fn run(
+        &self,
+        client: &Client,
+        serie: &Serie,
+        chapter: &Chapter,
+        urls: &[String],
+        output: &Path,
+        on_event: &mut dyn FnMut(ExportEvent),
+    ) -> Result<Vec<PdfWarning>, ChapterError> {
+        let folder = self.chapter_folder(serie, chapter);
+        fs::create_dir_all(&folder)?;
+
+        on_event(ExportEvent::Started(ExportStep::Download, urls.len()));
+        let mut pages = self.download(client, urls, &folder, on_event)?;
+        on_event(ExportEvent::Finished(ExportStep::Download, pages.len()));
+
+        if self.hash_pages {
+            on_event(ExportEvent::Hashes(hash_images(&pages)?));
+        }
+
+        if let Some(credits) = &self.credits {
+            on_event(ExportEvent::Started(ExportStep::Credits, pages.len()));
+            let scan = chapter.providers.first().map(|p| p.scan.as_str());
+            pages = drop_credit_pages(&pages, &credits.for_scan(scan.unwrap_or_default()))?;
+            on_event(ExportEvent::Finished(ExportStep::Credits, pages.len()));
+        }
+
+        if self.trim {
+            on_event(ExportEvent::Started(ExportStep::Trim, pages.len()));
+            let limit = self.trim_limit.clamp(0.0, 1.0);
+            pages = trim_borders(&pages, &folder, self.trim_tolerance, limit)?;
+            on_event(ExportEvent::Finished(ExportStep::Trim, pages.len()));
+        }
+
+        let direction = self.direction.unwrap_or(serie.url_info.reading_direction());
+
+        if self.split_spreads {
+            on_event(ExportEvent::Started(ExportStep::Spreads, pages.len()));
+            pages = split_spreads(&pages, &folder, direction)?;
+            on_event(ExportEvent::Finished(ExportStep::Spreads, pages.len()));
+        }
+
+        if let Some(ratio) = self.split_tall {
+            on_event(ExportEvent::Started(ExportStep::Split, pages.len()));
+            pages = split_tall_images(&pages, &folder, ratio)?;
+            on_event(ExportEvent::Finished(ExportStep::Split, pages.len()));
+        }
+
+        if let Some(max_height) = self.stitch {
+            on_event(ExportEvent::Started(ExportStep::Stitch, pages.len()));
+            pages = stitch_images(&pages, &folder, max_height)?;
+            on_event(ExportEvent::Finished(ExportStep::Stitch, pages.len()));
+        }
+
+        if self.convert.format.is_some() || self.convert.max_width.is_some() {
+            on_event(ExportEvent::Started(ExportStep::Convert, pages.len()));
+            pages = convert_images(&pages, &folder, &self.convert)?;
+            on_event(ExportEvent::Finished(ExportStep::Convert, pages.len()));
+        }
+
+        if self.cover {
+            match &serie.cover_url {
+                Some(cover_url) => {
+                    let cover = client.download_page(cover_url, &folder, "cover")?;
+                    pages.insert(0, cover);
+                    info!(url = cover_url.as_str(); "cover added");
+                }
+                None => warn!("the serie has no cover, skipping it"),
+            }
+        }
+
+        let mut format = self.format.unwrap_or(OutputFormat::Pdf);
+        if let Some(profile) = self.profile {
+            let settings = profile.settings();
+            on_event(ExportEvent::Started(ExportStep::Profile, pages.len()));
+            pages = apply_profile(
+                &pages,
+                &folder,
+                &settings,
+                self.convert.format,
+                self.convert.quality,
+            )?;
+            on_event(ExportEvent::Finished(ExportStep::Profile, pages.len()));
+
+            if self.format.is_none() {
+                format = match settings.container {
+                    PreferredContainer::Pdf => OutputFormat::Pdf,
+                    PreferredContainer::Cbz => OutputFormat::Cbz,
+                };
+            }
+        }
+
+        on_event(ExportEvent::Started(ExportStep::Output, pages.len()));
+        let scan = chapter.providers.first().map(|p| p.scan.as_str());
+        let metadata = Metadata::new(serie, chapter, scan.unwrap_or_default());
+        let mut warnings: Vec<PdfWarning> = Vec::new();
+        match format {
+            OutputFormat::Pdf => {
+                let file = File::create(output.with_added_extension("pdf"))?;
+                let mut options = self.pdf.clone();
+                options.skip_broken |= self.skip_broken_pages;
+                warnings = create_pdf(file, &metadata, &pages, direction, &options)?;
+            }
+            OutputFormat::Cbz => {
+                let file = File::create(output.with_added_extension("cbz"))?;
+                create_cbz(file, &metadata, &pages, direction)?;
+            }
+            OutputFormat::Images => save_images(output, &pages)?,
+        }
+        on_event(ExportEvent::Finished(ExportStep::Output, pages.len()));
+        return Ok(warnings);
+    }
+
+    // Downloads every image into `folder`, replacing the broken ones with a
+    // placeholder when `skip_broken_pages` is set.
+    fn download(
+        &self,
+        client: &Client,
+        urls: &[String],
+        folder: &Path,
+        on_event: &mut dyn FnMut(ExportEvent),
+    ) -> Result<Vec<Page>, ChapterError> {
+        let mut pages: Vec<Page> = Vec::new();
+        for (i, url) in urls.iter().enumerate() {
+            let page = match client.download_page(url, folder, &i.to_string()) {
+                Ok(page) => page,
+                // Write errors are never skipped, the next pages would fail too.
+                Err(DownloadError::Io(error)) => return Err(DownloadError::Io(error).into()),
+                Err(error) if self.skip_broken_pages => {
+                    warn!(page = i + 1, url = url.as_str(), error:%; "page replaced by a placeholder");
+                    // The placeholder takes the size of the previous page.
+                    let (width, height) = pages
+                        .last()
+                        .and_then(|page| image::image_dimensions(&page.path).ok())
+                        .unwrap_or((800, 1200));
+                    placeholder_page(folder, &i.to_string(), url, width, height)?
+                }
+                Err(error) => return Err(error.into()),
+            };
+            let bytes = fs::metadata(&page.path).map(|m| m.len()).unwrap_or(0);
+            on_event(ExportEvent::PageSaved(bytes));
+            pages.push(page);
+        }
+        return Ok(pages);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::serie::{Provider, SerieUrlInfo};
+    use std::net::TcpListener;
+
+    #[test]
+    fn broken_pages_are_replaced_and_written() {
+        let folder = std::env::temp_dir().join(format!("chapter-export-{}", std::process::id()));
+        let url_info = SerieUrlInfo::new(
+            "https://zonatmo.com/library/manga/42/slug".to_string(),
+            42,
+            "slug".to_string(),
+            false,
+            "manga".to_string(),
+            "tmo".to_string(),
+        );
+        let serie = Serie::new(url_info, "Serie".to_string(), None, Vec::new());
+        let provider = Provider::new("Scan".to_string(), String::new());
+        let chapter = Chapter::new((1, 0), "Chapter 1".to_string(), vec![provider]);
+        // Nothing listens on the port once the listener is dropped.
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let urls = vec![format!("http://{}/1.png", address)];
+
+        let mut export = ChapterExport::new(folder.join("cache"));
+        export.format = Some(OutputFormat::Images);
+        let client = Client::new().with_retries(0);
+        let output = folder.join("output");
+        let mut events: Vec<ExportEvent> = Vec::new();
+        let result = export.run(&client, &serie, &chapter, &urls, &output, &mut |event| {
+            events.push(event)
+        });
+        assert!(matches!(result, Err(ChapterError::Download(_))));
+
+        export.skip_broken_pages = true;
+        export
+            .run(&client, &serie, &chapter, &urls, &output, &mut |event| {
+                events.push(event)
+            })
+            .unwrap();
+        assert!(output.join("0001.png").is_file());
+        let missing = fs::read_to_string(output.join("missing.txt")).unwrap();
+        assert_eq!(
+            missing,
+            format!("The image of page 1 is missing: {}\n", urls[0])
+        );
+        assert!(matches!(
+            events.last(),
+            Some(ExportEvent::Finished(ExportStep::Output, 1))
+        ));
+        let _ = fs::remove_dir_all(&folder);
+    }
+}
@@ -0,0 +1,33 @@
+use super::missing_pages;
+use crate::errors::ExportError;
+use crate::processing::Page;
+use std::fs;
+use std::path::Path;
+
+/// Name of the file listing the pages that could not be downloaded.
+const MISSING_FILE: &str = "missing.txt";
+
+/// Copies the images into `folder` with sequential names keeping their extension.
+/// Placeholders of missing pages are listed in `missing.txt`.
+pub fn save_images(folder: &Path, pages: &[Page]) -> Result<(), ExportError> {
+    fs::create_dir_all(folder)?;
+
+    for (i, page) in pages.iter().enumerate() {
+        let extension = page
+            .path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("img");
+        fs::copy(
+            &page.path,
+            folder.join(format!("{:04}.{}", i + 1, extension)),
+        )?;
+    }
+
+    let missing = missing_pages(pages);
+    if !missing.is_empty() {
+        fs::write(folder.join(MISSING_FILE), missing.join("\n") + "\n")?;
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,96 @@
+/// Letter page width in points.
+pub const LETTER_WIDTH: f32 = 8.5 * 72.0;
+
+/// Largest page side most PDF viewers accept, 200 inches in points.
+pub const MAX_PAGE_SIDE: f32 = 200.0 * 72.0;
+
+/// Size in points of a page that shows a whole image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageLayout {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PageLayout {
+    /// Fits the image to a letter width page keeping its aspect ratio.
+    /// Strips taller than the maximum page side are scaled down as a whole.
+    pub fn fit_width(image_width: u32, image_height: u32) -> PageLayout {
+        let ratio = image_height.max(1) as f32 / image_width.max(1) as f32;
+        let mut width = LETTER_WIDTH;
+        let mut height = ratio * LETTER_WIDTH;
+
+        if height > MAX_PAGE_SIDE {
+            width = MAX_PAGE_SIDE / ratio;
+            height = MAX_PAGE_SIDE;
+        }
+
+        return PageLayout { width, height };
+    }
+
+    /// Content stream that draws an image XObject over the whole page.
+    pub fn draw_image(&self, name: &str) -> String {
+        return format!(
+            "q {:.2} 0 0 {:.2} 0 0 cm /{} Do Q",
+            self.width, self.height, name
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_ratio(layout: PageLayout, width: u32, height: u32) {
+        let expected = height as f32 / width as f32;
+        let actual = layout.height / layout.width;
+        assert!(
+            (expected - actual).abs() < 1e-4,
+            "{} != {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn portrait_keeps_ratio() {
+        let layout = PageLayout::fit_width(800, 1200);
+        assert_eq!(layout.width, LETTER_WIDTH);
+        assert_eq!(layout.height, LETTER_WIDTH * 1.5);
+        assert_ratio(layout, 800, 1200);
+    }
+
+    #[test]
+    fn non_integer_ratio_is_not_truncated() {
+        let layout = PageLayout::fit_width(1000, 1414);
+        assert!(layout.height > LETTER_WIDTH * 1.4);
+        assert_ratio(layout, 1000, 1414);
+    }
+
+    #[test]
+    fn landscape_is_shorter_than_wide() {
+        let layout = PageLayout::fit_width(1600, 1200);
+        assert_eq!(layout.width, LETTER_WIDTH);
+        assert_eq!(layout.height, LETTER_WIDTH * 0.75);
+        assert_ratio(layout, 1600, 1200);
+    }
+
+    #[test]
+    fn tall_strip_fits_maximum_page_side() {
+        let layout = PageLayout::fit_width(720, 30000);
+        assert_eq!(layout.height, MAX_PAGE_SIDE);
+        assert!(layout.width < LETTER_WIDTH);
+        assert_ratio(layout, 720, 30000);
+    }
+
+    #[test]
+    fn draw_image_fills_page() {
+        let layout = PageLayout {
+            width: 612.0,
+            height: 918.0,
+        };
+        assert_eq!(
+            layout.draw_image("Im0"),
+            "q 612.00 0 0 918.00 0 0 cm /Im0 Do Q"
+        );
+    }
+}
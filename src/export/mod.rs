@@ -0,0 +1,59 @@
+pub mod cbz;
+pub mod chapter;
+pub mod images;
+pub mod layout;
+pub mod pdf;
+
+use crate::models::serie::{Chapter, Serie};
+use crate::processing::Page;
+
+/// Document information shared by every export format.
+#[non_exhaustive]
+pub struct Metadata {
+    pub series: String,
+    pub chapter: String,
+    pub number: String,
+    pub scan: String,
+    pub kind: String,
+    pub url: String,
+}
+
+impl Metadata {
+    pub fn new(serie: &Serie, chapter: &Chapter, scan: &str) -> Metadata {
+        let number = match chapter.number {
+            (complete, 0) => complete.to_string(),
+            (complete, decimal) => format!("{}.{:02}", complete, decimal),
+        };
+
+        return Metadata {
+            series: serie.title.clone(),
+            chapter: chapter.name.clone(),
+            number,
+            scan: scan.to_string(),
+            kind: serie.url_info.kind.clone(),
+            url: serie.url_info.url.clone(),
+        };
+    }
+
+    /// Full title of the document, one shots only use the series title.
+    pub fn title(&self) -> String {
+        if self.chapter == self.series {
+            return self.series.clone();
+        }
+        return format!("{} - {}", self.series, self.chapter);
+    }
+
+    pub fn producer() -> String {
+        return format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    }
+}
+
+/// One line for every page replaced by a placeholder, with its number and url.
+pub(crate) fn missing_pages(pages: &[Page]) -> Vec<String> {
+    return pages
+        .iter()
+        .enumerate()
+        .filter(|(_, page)| page.placeholder)
+        .map(|(i, page)| format!("The image of page {} is missing: {}", i + 1, page.source))
+        .collect();
+}
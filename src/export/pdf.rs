@@ -0,0 +1,513 @@
+use super::Metadata;
+use super::layout::{LETTER_WIDTH, PageLayout};
+use crate::errors::ExportError;
+use crate::models::serie::ReadingDirection;
+use crate::processing::Page;
+use crate::processing::convert::{ConvertFormat, encode_image};
+use chrono::Utc;
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use image::codecs::jpeg::JpegDecoder;
+use image::{DynamicImage, ExtendedColorType, ImageDecoder, ImageFormat, ImageReader};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Cursor, Write};
+
+// Objects written at the end, their ids are reserved when the writer is created.
+const CATALOG_ID: u32 = 1;
+const PAGES_ID: u32 = 2;
+const INFO_ID: u32 = 3;
+
+/// How WebP pages are embedded, PDF has no native support for them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[non_exhaustive]
+pub enum WebpEmbedding {
+    /// Lossless, decoded pixels compressed with Flate.
+    Flate,
+    /// Transcoded to JPEG, smaller but lossy.
+    Jpeg,
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PdfOptions {
+    pub webp: WebpEmbedding,
+    /// JPEG quality used when transcoding.
+    pub quality: u8,
+    /// Replace pages that can not be decoded with a placeholder instead of failing.
+    pub skip_broken: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        return PdfOptions {
+            webp: WebpEmbedding::Flate,
+            quality: 85,
+            skip_broken: false,
+        };
+    }
+}
+
+/// Something that was changed or lost while embedding a page.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PdfWarning {
+    /// Page number, starting at 1.
+    pub page: usize,
+    pub source: String,
+    pub message: String,
+}
+
+impl fmt::Display for PdfWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Page {} ({}): {}", self.page, self.source, self.message)
+    }
+}
+
+/// Writes the images as a PDF with one page per image.
+/// Pages are streamed to the file one by one, so only one image is kept in memory.
+/// JPEG pages are embedded as they are, without decoding them.
+pub fn create_pdf(
+    file: File,
+    metadata: &Metadata,
+    pages: &[Page],
+    direction: ReadingDirection,
+    options: &PdfOptions,
+) -> Result<Vec<PdfWarning>, ExportError> {
+    let mut writer = PdfWriter::new(BufWriter::new(file))?;
+    let mut warnings: Vec<PdfWarning> = Vec::new();
+
+    for (i, page) in pages.iter().enumerate() {
+        // Missing pages were already reported when downloading them.
+        let result = match page.placeholder {
+            true => writer.add_placeholder_page(i + 1, &page.source),
+            false => embed_page(&mut writer, page, options),
+        };
+        let mut messages = match result {
+            Ok(()) => writer.take_warnings(),
+            // Write errors leave the file broken, they are never skipped.
+            Err(ExportError::Io(error)) => return Err(ExportError::Io(error)),
+            Err(error) if options.skip_broken => {
+                writer.add_placeholder_page(i + 1, &page.source)?;
+                vec![format!("replaced by a placeholder, {}", error)]
+            }
+            Err(error) => {
+                return Err(ExportError::BrokenPage {
+                    page: i + 1,
+                    url: page.source.clone(),
+                    error: Box::new(error),
+                });
+            }
+        };
+
+        warnings.extend(messages.drain(..).map(|message| PdfWarning {
+            page: i + 1,
+            source: page.source.clone(),
+            message,
+        }));
+    }
+
+    writer.finish(metadata, direction)?.flush()?;
+    return Ok(warnings);
+}
+
+fn embed_page<W: Write>(
+    writer: &mut PdfWriter<W>,
+    page: &Page,
+    options: &PdfOptions,
+) -> Result<(), ExportError> {
+    let bytes = fs::read(&page.path)?;
+    let reader = ImageReader::new(Cursor::new(&bytes)).with_guessed_format()?;
+
+    match reader.format() {
+        Some(ImageFormat::Jpeg) => writer.add_jpeg_page(&bytes)?,
+        Some(ImageFormat::WebP) if options.webp == WebpEmbedding::Jpeg => {
+            let mut jpeg: Vec<u8> = Vec::new();
+            encode_image(
+                &reader.decode()?,
+                &mut jpeg,
+                ConvertFormat::Jpeg,
+                options.quality,
+            )?;
+            writer.add_jpeg_page(&jpeg)?;
+        }
+        _ => writer.add_image_page(&reader.decode()?)?,
+    };
+
+    return Ok(());
+}
+
+/// Minimal PDF writer that outputs every object as soon as it is added.
+/// Only the byte offsets of the objects and the page ids are kept until the end.
+pub struct PdfWriter<W: Write> {
+    output: W,
+    position: u64,
+    offsets: Vec<u64>,
+    pages: Vec<u32>,
+    warnings: Vec<String>,
+}
+
+impl<W: Write> PdfWriter<W> {
+    pub fn new(output: W) -> Result<Self, ExportError> {
+        let mut writer = PdfWriter {
+            output,
+            position: 0,
+            // Catalog, pages tree and info are written last.
+            offsets: vec![0; 3],
+            pages: Vec::new(),
+            warnings: Vec::new(),
+        };
+        // The binary comment tells transfer tools the file is not plain text.
+        writer.write(b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n")?;
+        return Ok(writer);
+    }
+
+    /// Adds a page that shows the whole image, compressing its pixels with Flate.
+    pub fn add_image_page(&mut self, image: &DynamicImage) -> Result<(), ExportError> {
+        let color = image.color();
+        if color.has_alpha() {
+            self.warnings
+                .push("the transparency was discarded".to_string());
+        }
+        if color.bytes_per_pixel() > color.channel_count() {
+            self.warnings
+                .push("the color depth was reduced to 8 bits".to_string());
+        }
+
+        let (color_space, pixels) = match image {
+            DynamicImage::ImageLuma8(_) | DynamicImage::ImageLumaA8(_) => {
+                ("DeviceGray", image.to_luma8().into_raw())
+            }
+            _ => ("DeviceRGB", image.to_rgb8().into_raw()),
+        };
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&pixels)?;
+        let data = encoder.finish()?;
+
+        return self.add_page(
+            image.width(),
+            image.height(),
+            color_space,
+            "FlateDecode",
+            &data,
+        );
+    }
+
+    /// Adds a page with the JPEG stream embedded directly.
+    /// Color spaces PDF viewers can not show as they are (CMYK) are decoded instead.
+    pub fn add_jpeg_page(&mut self, jpeg: &[u8]) -> Result<(), ExportError> {
+        let decoder = JpegDecoder::new(Cursor::new(jpeg))?;
+        let (width, height) = decoder.dimensions();
+
+        let color_space = match decoder.original_color_type() {
+            ExtendedColorType::L8 => "DeviceGray",
+            ExtendedColorType::Rgb8 => "DeviceRGB",
+            other => {
+                self.warnings.push(format!(
+                    "the JPEG color type {:?} can not be embedded, the page was decoded",
+                    other
+                ));
+                return self.add_image_page(&DynamicImage::from_decoder(decoder)?);
+            }
+        };
+
+        return self.add_page(width, height, color_space, "DCTDecode", jpeg);
+    }
+
+    fn add_page(
+        &mut self,
+        width: u32,
+        height: u32,
+        color_space: &str,
+        filter: &str,
+        data: &[u8],
+    ) -> Result<(), ExportError> {
+        let image_id = self.next_id();
+        self.write_stream(
+            image_id,
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /{} /BitsPerComponent 8 /Filter /{}",
+                width, height, color_space, filter
+            ),
+            data,
+        )?;
+
+        let layout = PageLayout::fit_width(width, height);
+        return self.write_page(
+            layout,
+            &format!("/XObject << /Im0 {} 0 R >>", image_id),
+            &layout.draw_image("Im0"),
+        );
+    }
+
+    /// Adds a letter page noting that the image of the page is missing.
+    pub fn add_placeholder_page(&mut self, page: usize, source: &str) -> Result<(), ExportError> {
+        let layout = PageLayout {
+            width: LETTER_WIDTH,
+            height: 11.0 * 72.0,
+        };
+
+        let mut content = format!(
+            "BT /F1 18 Tf 72 {:.2} Td ({}) Tj /F1 10 Tf",
+            layout.height - 108.0,
+            literal_string(&format!("The image of page {} is missing.", page))
+        );
+        // Long urls are wrapped so they fit in the page.
+        let characters: Vec<char> = source.chars().collect();
+        for line in characters.chunks(90) {
+            let line: String = line.iter().collect();
+            content.push_str(&format!(" 0 -16 Td ({}) Tj", literal_string(&line)));
+        }
+        content.push_str(" ET");
+
+        return self.write_page(
+            layout,
+            "/Font << /F1 << /Type /Font /Subtype /Type1 /BaseFont /Helvetica >> >>",
+            &content,
+        );
+    }
+
+    /// Returns the warnings of the pages added since the last call.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        return std::mem::take(&mut self.warnings);
+    }
+
+    fn write_page(
+        &mut self,
+        layout: PageLayout,
+        resources: &str,
+        content: &str,
+    ) -> Result<(), ExportError> {
+        let content_id = self.next_id();
+        self.write_stream(content_id, "", content.as_bytes())?;
+
+        let page_id = self.next_id();
+        self.write_object(
+            page_id,
+            &format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << {} >> /Contents {} 0 R >>",
+                PAGES_ID, layout.width, layout.height, resources, content_id
+            ),
+        )?;
+        self.pages.push(page_id);
+
+        return Ok(());
+    }
+
+    /// Writes the document structure and the cross reference table.
+    pub fn finish(
+        mut self,
+        metadata: &Metadata,
+        direction: ReadingDirection,
+    ) -> Result<W, ExportError> {
+        let kids: Vec<String> = self.pages.iter().map(|id| format!("{} 0 R", id)).collect();
+        self.write_object(
+            PAGES_ID,
+            &format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            ),
+        )?;
+
+        let preferences = match direction {
+            ReadingDirection::Rtl => "/ViewerPreferences << /Direction /R2L >> ",
+            ReadingDirection::Ltr => "",
+        };
+        self.write_object(
+            CATALOG_ID,
+            &format!(
+                "<< /Type /Catalog /Pages {} 0 R {}>>",
+                PAGES_ID, preferences
+            ),
+        )?;
+
+        let keywords = [
+            metadata.series.as_str(),
+            metadata.kind.as_str(),
+            metadata.scan.as_str(),
+        ];
+        let date = Utc::now().format("D:%Y%m%d%H%M%SZ").to_string();
+        // The scan is the only author known, it also prepared the pages. The page
+        // of the serie goes in a custom key, the info dictionary has no standard one.
+        self.write_object(
+            INFO_ID,
+            &format!(
+                "<< /Title {} /Author {} /Subject {} /Keywords {} /Creator {} /Producer {} /SourceURL {} /CreationDate ({}) /ModDate ({}) >>",
+                text_string(&metadata.title()),
+                text_string(&metadata.scan),
+                text_string(&metadata.series),
+                text_string(&keywords.join(", ")),
+                text_string(&metadata.scan),
+                text_string(&Metadata::producer()),
+                text_string(&metadata.url),
+                date,
+                date
+            ),
+        )?;
+
+        let xref_position = self.position;
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            xref.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            CATALOG_ID,
+            INFO_ID,
+            xref_position
+        ));
+        self.write(xref.as_bytes())?;
+
+        return Ok(self.output);
+    }
+
+    fn next_id(&mut self) -> u32 {
+        self.offsets.push(0);
+        return self.offsets.len() as u32;
+    }
+
+    fn write_object(&mut self, id: u32, body: &str) -> Result<(), ExportError> {
+        self.offsets[id as usize - 1] = self.position;
+        self.write(format!("{} 0 obj\n{}\nendobj\n", id, body).as_bytes())?;
+        return Ok(());
+    }
+
+    fn write_stream(&mut self, id: u32, dictionary: &str, data: &[u8]) -> Result<(), ExportError> {
+        self.offsets[id as usize - 1] = self.position;
+        self.write(
+            format!(
+                "{} 0 obj\n<< {} /Length {} >>\nstream\n",
+                id,
+                dictionary,
+                data.len()
+            )
+            .as_bytes(),
+        )?;
+        self.write(data)?;
+        self.write(b"\nendstream\nendobj\n")?;
+        return Ok(());
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), ExportError> {
+        self.output.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        return Ok(());
+    }
+}
+
+/// Encodes a PDF text string as UTF-16BE so non ASCII titles are displayed correctly.
+fn text_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        hex.push_str(&format!("{:04X}", unit));
+    }
+    hex.push('>');
+    return hex;
+}
+
+/// Escapes a PDF literal string, characters outside ASCII are replaced
+/// because the standard fonts are not Unicode.
+fn literal_string(text: &str) -> String {
+    let mut escaped = String::new();
+    for character in text.chars() {
+        match character {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            ' '..='~' => escaped.push(character),
+            _ => escaped.push('?'),
+        }
+    }
+    return escaped;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::serie::{Chapter, Provider, Serie, SerieUrlInfo};
+    use image::GrayImage;
+
+    #[test]
+    fn info_dictionary_has_the_document_metadata() {
+        let url_info = SerieUrlInfo::new(
+            "https://zonatmo.com/library/manga/1/serie".to_string(),
+            1,
+            "serie".to_string(),
+            false,
+            "manga".to_string(),
+            "tmo".to_string(),
+        );
+        let provider = Provider::new("Scan".to_string(), String::new());
+        let chapter = Chapter::new((1, 0), "Capítulo 1.00".to_string(), vec![provider]);
+        let serie = Serie::new(url_info, "Serie".to_string(), None, vec![chapter]);
+        let metadata = Metadata::new(&serie, &serie.chapters[0], "Scan");
+
+        let mut writer = PdfWriter::new(Vec::new()).unwrap();
+        let page = DynamicImage::ImageLuma8(GrayImage::new(2, 2));
+        writer.add_image_page(&page).unwrap();
+        let pdf = writer.finish(&metadata, ReadingDirection::Rtl).unwrap();
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        let expected = [
+            format!("/Title {}", text_string("Serie - Capítulo 1.00")),
+            format!("/Author {}", text_string("Scan")),
+            format!("/Subject {}", text_string("Serie")),
+            format!("/Keywords {}", text_string("Serie, manga, Scan")),
+            format!("/Creator {}", text_string("Scan")),
+            format!("/Producer {}", text_string(&Metadata::producer())),
+            format!(
+                "/SourceURL {}",
+                text_string("https://zonatmo.com/library/manga/1/serie")
+            ),
+        ];
+        for key in expected {
+            assert!(pdf.contains(&key), "missing {}", key);
+        }
+        assert!(pdf.contains("/CreationDate (D:"));
+        assert!(pdf.contains("/ModDate (D:"));
+        assert!(pdf.contains("/Direction /R2L"));
+    }
+
+    #[test]
+    fn placeholders_are_noted_without_reading_them() {
+        let url_info = SerieUrlInfo::new(
+            "https://zonatmo.com/library/manga/1/serie".to_string(),
+            1,
+            "serie".to_string(),
+            false,
+            "manga".to_string(),
+            "tmo".to_string(),
+        );
+        let serie = Serie::new(url_info, "Serie".to_string(), None, Vec::new());
+        let chapter = Chapter::new((1, 0), "Capítulo 1.00".to_string(), Vec::new());
+        let metadata = Metadata::new(&serie, &chapter, "Scan");
+        // The file does not exist, only the note is written.
+        let mut page = Page::new(
+            "missing.png".into(),
+            "https://img.example/1.png".to_string(),
+        );
+        page.placeholder = true;
+
+        let path = std::env::temp_dir().join(format!("placeholder-{}.pdf", std::process::id()));
+        let options = PdfOptions::default();
+        let warnings = create_pdf(
+            File::create(&path).unwrap(),
+            &metadata,
+            &[page],
+            ReadingDirection::Rtl,
+            &options,
+        )
+        .unwrap();
+        let pdf = String::from_utf8_lossy(&fs::read(&path).unwrap()).to_string();
+        let _ = fs::remove_file(&path);
+
+        assert!(warnings.is_empty());
+        assert!(pdf.contains("(The image of page 1 is missing.) Tj"));
+        assert!(pdf.contains("(https://img.example/1.png) Tj"));
+    }
+}
@@ -0,0 +1,438 @@
+use std::env;
+use std::fmt;
+use std::sync::OnceLock;
+
+// Log messages stay in English, they are meant to be searched and parsed.
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[non_exhaustive]
+pub enum Lang {
+    /// Español.
+    Es,
+    /// English.
+    En,
+}
+
+impl Lang {
+    /// Language of the environment, from `LC_ALL`, `LC_MESSAGES` or `LANG`.
+    pub fn from_env() -> Lang {
+        for key in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            match env::var(key) {
+                Ok(value) if value.starts_with("es") => return Lang::Es,
+                Ok(value) if !value.is_empty() => return Lang::En,
+                _ => continue,
+            }
+        }
+        return Lang::En;
+    }
+}
+
+/// Sets the language of every message, only the first call has effect.
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+pub fn lang() -> Lang {
+    return *LANG.get_or_init(Lang::from_env);
+}
+
+/// Every message shown to the user, displayed in the current language.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum Msg {
+    // Command line.
+    About,
+    UsageHeading,
+    ArgumentsHeading,
+    OptionsHeading,
+    CommandsHeading,
+    SearchAbout,
+    AddAbout,
+    RemoveAbout,
+    UpdateAbout,
+
+    // Progress.
+    SerieStep,
+    ChaptersFound(usize),
+    ChaptersStep,
+    ChaptersDone,
+    ReaderStep,
+    PagesFound(usize),
+    PagesStep,
+    PagesSaved,
+    CreditsStep,
+    CreditsDropped(usize),
+    TrimStep,
+    BordersTrimmed,
+    SpreadsStep,
+    SpreadsSplit(usize),
+    SplitStep,
+    TallSplit(usize),
+    StitchStep,
+    Stitched(usize),
+    ConvertStep,
+    Converted,
+    ProfileStep,
+    ProfileApplied,
+    OutputStep,
+    PagesWritten(usize),
+    PageHash(usize),
+
+    // Search.
+    SearchStep,
+    ResultsFound(usize),
+    NoResults,
+    PickResult,
+    PickAction,
+    ChapterList,
+
+    // Library.
+    Following(usize),
+    NotFollowed(u32),
+    Unfollowed(u32),
+    LibraryEmpty,
+    NewChapters(usize),
+
+    // Interactive picker.
+    PickerHelp,
+    Downloaded,
+
+    // Errors.
+    CacheIo,
+    CacheNotFound,
+    CacheExpired,
+    CacheParse,
+    MissingTitle,
+    MissingScan,
+    UnsupportedUrl,
+    InvalidRatio,
+    InvalidValue,
+    UnknownHost,
+    MissingPath,
+    InvalidFormat,
+    InvalidId,
+    DownloadsFailed(usize),
+    CommandFailed,
+}
+
+impl fmt::Display for Msg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match lang() {
+            Lang::Es => spanish(self, f),
+            Lang::En => english(self, f),
+        };
+    }
+}
+
+fn spanish(msg: &Msg, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    return match msg {
+        Msg::About => write!(f, "Descarga capítulos de TMO como PDF, CBZ o imágenes."),
+        Msg::UsageHeading => write!(f, "Uso:"),
+        Msg::SearchAbout => write!(
+            f,
+            "Busca una serie por su título y elige una para listar o descargar."
+        ),
+        Msg::AddAbout => write!(
+            f,
+            "Sigue una serie, `update` descarga los capítulos publicados después."
+        ),
+        Msg::RemoveAbout => write!(f, "Deja de seguir una serie."),
+        Msg::UpdateAbout => write!(f, "Descarga los capítulos nuevos de cada serie seguida."),
+        Msg::ArgumentsHeading => write!(f, "Argumentos"),
+        Msg::OptionsHeading => write!(f, "Opciones"),
+        Msg::CommandsHeading => write!(f, "Comandos"),
+
+        Msg::SerieStep => write!(f, "Serie"),
+        Msg::ChaptersFound(n) => write!(f, "{} capítulos encontrados", n),
+        Msg::ChaptersStep => write!(f, "Capítulos"),
+        Msg::ChaptersDone => write!(f, "listo"),
+        Msg::ReaderStep => write!(f, "Lector"),
+        Msg::PagesFound(n) => write!(f, "{} páginas encontradas", n),
+        Msg::PagesStep => write!(f, "Páginas"),
+        Msg::PagesSaved => write!(f, "todas las páginas guardadas en disco"),
+        Msg::CreditsStep => write!(f, "Créditos"),
+        Msg::CreditsDropped(n) => write!(f, "{} páginas de créditos eliminadas", n),
+        Msg::TrimStep => write!(f, "Recorte"),
+        Msg::BordersTrimmed => write!(f, "bordes recortados"),
+        Msg::SpreadsStep => write!(f, "Dobles"),
+        Msg::SpreadsSplit(n) => write!(f, "páginas dobles separadas, ahora hay {} páginas", n),
+        Msg::SplitStep => write!(f, "División"),
+        Msg::TallSplit(n) => write!(f, "imágenes largas divididas en {} páginas", n),
+        Msg::StitchStep => write!(f, "Unión"),
+        Msg::Stitched(n) => write!(f, "imágenes unidas en {} tiras", n),
+        Msg::ConvertStep => write!(f, "Conversión"),
+        Msg::Converted => write!(f, "imágenes convertidas"),
+        Msg::ProfileStep => write!(f, "Perfil"),
+        Msg::ProfileApplied => write!(f, "imágenes adaptadas al perfil del dispositivo"),
+        Msg::OutputStep => write!(f, "Salida"),
+        Msg::PagesWritten(n) => write!(f, "{} páginas escritas", n),
+        Msg::PageHash(n) => write!(f, "página {}", n),
+
+        Msg::SearchStep => write!(f, "Búsqueda"),
+        Msg::ResultsFound(n) => write!(f, "{} resultados", n),
+        Msg::NoResults => write!(f, "No se encontró ninguna serie."),
+        Msg::PickResult => write!(f, "Número de la serie (vacío para salir): "),
+        Msg::PickAction => write!(f, "[l]istar capítulos o [d]escargar el último: "),
+        Msg::ChapterList => write!(f, "Capítulos"),
+
+        Msg::PickerHelp => write!(
+            f,
+            "↑↓ mover  espacio marcar  a todos  ←→ scan  enter descargar  q salir"
+        ),
+        Msg::Downloaded => write!(f, "descargado"),
+
+        Msg::Following(n) => write!(f, "seguida, {} capítulos ya publicados", n),
+        Msg::NotFollowed(index) => write!(f, "La serie {} no está en la biblioteca.", index),
+        Msg::Unfollowed(index) => write!(f, "La serie {} ya no se sigue.", index),
+        Msg::LibraryEmpty => write!(f, "La biblioteca está vacía, agrega series con `add`."),
+        Msg::NewChapters(n) => write!(f, "{} capítulos nuevos", n),
+
+        Msg::CacheIo => write!(f, "No se pudo leer la carpeta de caché"),
+        Msg::CacheNotFound => write!(f, "La caché de este manga no existe o no es válida."),
+        Msg::CacheExpired => write!(f, "El archivo de caché está vencido."),
+        Msg::CacheParse => write!(f, "No se pudo interpretar el contenido JSON"),
+        Msg::MissingTitle => write!(f, "El documento no contiene el elemento del título."),
+        Msg::MissingScan => {
+            write!(
+                f,
+                "El fragmento no contiene el elemento con el nombre del scan."
+            )
+        }
+        Msg::UnsupportedUrl => write!(f, "La URL no es compatible."),
+        Msg::InvalidRatio => write!(f, "Debe ser un número positivo."),
+        Msg::InvalidValue => write!(f, "El valor debe ser un ID numérico o una URL válida."),
+        Msg::UnknownHost => write!(f, "La URL no es de un sitio compatible."),
+        Msg::MissingPath => write!(f, "La URL de TMO debe tener una ruta con segmentos."),
+        Msg::InvalidFormat => write!(
+            f,
+            "Formato de URL no válido. Debe ser similar a https://zonatmo.com/library/manga/12345/nombre."
+        ),
+        Msg::InvalidId => write!(f, "La URL no contiene un ID numérico de manga válido."),
+        Msg::DownloadsFailed(n) => write!(f, "{} descargas fallaron.", n),
+        Msg::CommandFailed => write!(f, "No se pudo completar el comando"),
+    };
+}
+
+fn english(msg: &Msg, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    return match msg {
+        Msg::About => write!(f, "Downloads TMO chapters as PDF, CBZ or images."),
+        Msg::SearchAbout => write!(
+            f,
+            "Search a serie by its title and pick one to list or download."
+        ),
+        Msg::AddAbout => write!(
+            f,
+            "Follow a serie, `update` downloads the chapters released after this."
+        ),
+        Msg::RemoveAbout => write!(f, "Stop following a serie."),
+        Msg::UpdateAbout => write!(f, "Download the new chapters of every followed serie."),
+        Msg::UsageHeading => write!(f, "Usage:"),
+        Msg::ArgumentsHeading => write!(f, "Arguments"),
+        Msg::OptionsHeading => write!(f, "Options"),
+        Msg::CommandsHeading => write!(f, "Commands"),
+
+        Msg::SerieStep => write!(f, "Serie"),
+        Msg::ChaptersFound(n) => write!(f, "{} chapters found", n),
+        Msg::ChaptersStep => write!(f, "Chapters"),
+        Msg::ChaptersDone => write!(f, "done"),
+        Msg::ReaderStep => write!(f, "Reader"),
+        Msg::PagesFound(n) => write!(f, "{} pages found", n),
+        Msg::PagesStep => write!(f, "Pages"),
+        Msg::PagesSaved => write!(f, "all pages saved on disk"),
+        Msg::CreditsStep => write!(f, "Credits"),
+        Msg::CreditsDropped(n) => write!(f, "{} credit pages dropped", n),
+        Msg::TrimStep => write!(f, "Trim"),
+        Msg::BordersTrimmed => write!(f, "borders trimmed"),
+        Msg::SpreadsStep => write!(f, "Spreads"),
+        Msg::SpreadsSplit(n) => write!(f, "spreads splitted, {} pages now", n),
+        Msg::SplitStep => write!(f, "Split"),
+        Msg::TallSplit(n) => write!(f, "tall images splitted into {} pages", n),
+        Msg::StitchStep => write!(f, "Stitch"),
+        Msg::Stitched(n) => write!(f, "images stitched into {} strips", n),
+        Msg::ConvertStep => write!(f, "Convert"),
+        Msg::Converted => write!(f, "images converted"),
+        Msg::ProfileStep => write!(f, "Profile"),
+        Msg::ProfileApplied => write!(f, "images adapted to the device profile"),
+        Msg::OutputStep => write!(f, "Output"),
+        Msg::PagesWritten(n) => write!(f, "{} pages written", n),
+        Msg::PageHash(n) => write!(f, "page {}", n),
+
+        Msg::SearchStep => write!(f, "Search"),
+        Msg::ResultsFound(n) => write!(f, "{} results", n),
+        Msg::NoResults => write!(f, "No serie was found."),
+        Msg::PickResult => write!(f, "Number of the serie (empty to quit): "),
+        Msg::PickAction => write!(f, "[l]ist chapters or [d]ownload the last one: "),
+        Msg::ChapterList => write!(f, "Chapters"),
+
+        Msg::PickerHelp => write!(
+            f,
+            "↑↓ move  space mark  a all  ←→ scan  enter download  q quit"
+        ),
+        Msg::Downloaded => write!(f, "downloaded"),
+
+        Msg::Following(n) => write!(f, "followed, {} chapters already released", n),
+        Msg::NotFollowed(index) => write!(f, "The serie {} is not in the library.", index),
+        Msg::Unfollowed(index) => write!(f, "The serie {} is no longer followed.", index),
+        Msg::LibraryEmpty => write!(f, "The library is empty, add series with `add`."),
+        Msg::NewChapters(n) => write!(f, "{} new chapters", n),
+
+        Msg::CacheIo => write!(f, "Could not read the cache directory"),
+        Msg::CacheNotFound => write!(f, "Cache for this manga does not exist or is not valid."),
+        Msg::CacheExpired => write!(f, "The cache file is expired."),
+        Msg::CacheParse => write!(f, "Failed to parse JSON content"),
+        Msg::MissingTitle => write!(f, "Document do not contain title element."),
+        Msg::MissingScan => write!(f, "Fragment parsed do not contain scan name element."),
+        Msg::UnsupportedUrl => write!(f, "The URL is not supported."),
+        Msg::InvalidRatio => write!(f, "It must be a positive number."),
+        Msg::InvalidValue => write!(f, "The value must be a numeric ID or a valid URL."),
+        Msg::UnknownHost => write!(f, "The URL is not from a supported site."),
+        Msg::MissingPath => write!(f, "The TMO URL must have a path with segments."),
+        Msg::InvalidFormat => write!(
+            f,
+            "Invalid URL format. It should be similar to https://zonatmo.com/library/manga/12345/name."
+        ),
+        Msg::InvalidId => write!(f, "The URL does not contain a valid numeric manga ID."),
+        Msg::DownloadsFailed(n) => write!(f, "{} downloads failed.", n),
+        Msg::CommandFailed => write!(f, "The command failed"),
+    };
+}
+
+/// Help of every command line argument, by argument id.
+pub fn arg_help(id: &str) -> Option<&'static str> {
+    let help = match (lang(), id) {
+        (Lang::Es, "id") => "ID o URL del manga o manhwa a descargar",
+        (Lang::En, "id") => "ID or URL of the manga or manhwa to download",
+        (Lang::Es, "chapter") => "Número de capítulo a descargar",
+        (Lang::En, "chapter") => "Number of the chapter to download",
+        (Lang::Es, "range") => "Rango de capítulos a descargar (ej: 10-15, 25-end)",
+        (Lang::En, "range") => "Range of chapters to download (e.g. 10-15, 25-end)",
+        (Lang::Es, "all") => "Descargar todos los capítulos disponibles",
+        (Lang::En, "all") => "Download every available chapter",
+        (Lang::Es, "last") => {
+            "Descargar solo el último capítulo disponible (por defecto si no se especifica nada)"
+        }
+        (Lang::En, "last") => "Download only the last available chapter (default)",
+        (Lang::Es, "output") => "Ruta de salida para los archivos descargados",
+        (Lang::En, "output") => "Output path of the downloaded files",
+        (Lang::Es, "format") => {
+            "Formato de salida. Por defecto pdf, o el mejor para el perfil elegido."
+        }
+        (Lang::En, "format") => {
+            "Output format. Defaults to pdf, or to the best one for the selected profile."
+        }
+        (Lang::Es, "no_cache") => "No leer ni escribir la caché.",
+        (Lang::En, "no_cache") => "Avoid reading or writing to cache.",
+        (Lang::Es, "split_tall") => {
+            "Dividir las imágenes más altas que RATIO (alto / ancho) en trozos del tamaño de una página."
+        }
+        (Lang::En, "split_tall") => {
+            "Split images taller than RATIO (height / width) into page-sized chunks."
+        }
+        (Lang::Es, "stitch") => {
+            "Unir imágenes consecutivas en tiras continuas de hasta HEIGHT píxeles de alto."
+        }
+        (Lang::En, "stitch") => {
+            "Stitch consecutive images into continuous strips up to HEIGHT pixels tall."
+        }
+        (Lang::Es, "split_spreads") => {
+            "Separar las páginas dobles apaisadas en dos páginas según el sentido de lectura."
+        }
+        (Lang::En, "split_spreads") => {
+            "Split landscape double-page spreads into two pages following the reading direction."
+        }
+        (Lang::Es, "direction") => {
+            "Sentido de lectura de la salida. Por defecto rtl para manga y ltr para manhwa."
+        }
+        (Lang::En, "direction") => {
+            "Reading direction of the output. Defaults to rtl for manga and ltr for manhwa."
+        }
+        (Lang::Es, "convert") => "Convertir las imágenes a otro formato.",
+        (Lang::En, "convert") => "Convert the images to another format.",
+        (Lang::Es, "quality") => "Calidad usada al convertir a JPEG (1-100).",
+        (Lang::En, "quality") => "Quality used when converting to JPEG (1-100).",
+        (Lang::Es, "max_width") => "Reducir las imágenes más anchas que WIDTH píxeles.",
+        (Lang::En, "max_width") => "Downscale images wider than WIDTH pixels.",
+        (Lang::Es, "profile") => "Optimizar las imágenes para un dispositivo de lectura.",
+        (Lang::En, "profile") => "Optimize the images for a reading device.",
+        (Lang::Es, "trim") => "Recortar los bordes blancos o negros uniformes de las páginas.",
+        (Lang::En, "trim") => "Crop uniform white or black borders of the pages.",
+        (Lang::Es, "trim_tolerance") => {
+            "Diferencia máxima de luma (0-255) para que un píxel se considere parte del borde."
+        }
+        (Lang::En, "trim_tolerance") => {
+            "Maximum luma difference (0-255) of a pixel to be considered part of the border."
+        }
+        (Lang::Es, "trim_limit") => "Fracción máxima (0-1) de cada lado que se puede recortar.",
+        (Lang::En, "trim_limit") => "Maximum fraction (0-1) of each side that can be trimmed.",
+        (Lang::Es, "drop_credits") => {
+            "Quitar las páginas de créditos y reclutamiento que coinciden con los hashes conocidos del scan."
+        }
+        (Lang::En, "drop_credits") => {
+            "Drop credit and recruitment pages matching the known hashes of the scan."
+        }
+        (Lang::Es, "credit_hashes") => {
+            "Archivo JSON con los hashes de las páginas de créditos de cada scan."
+        }
+        (Lang::En, "credit_hashes") => "JSON file with the credit page hashes of every scan.",
+        (Lang::Es, "show_hashes") => {
+            "Mostrar el hash perceptual de cada página, para registrar nuevas páginas de créditos."
+        }
+        (Lang::En, "show_hashes") => {
+            "Print the perceptual hash of every page, to register new credit pages."
+        }
+        (Lang::Es, "cover") => "Agregar la portada de la serie como primera página.",
+        (Lang::En, "cover") => "Prepend the series cover as the first page.",
+        (Lang::Es, "pdf_webp") => {
+            "Cómo se insertan las páginas WebP en el PDF. Jpeg usa la calidad indicada."
+        }
+        (Lang::En, "pdf_webp") => {
+            "How WebP pages are embedded in the PDF. Jpeg uses the quality setting."
+        }
+        (Lang::Es, "skip_broken_pages") => {
+            "Reemplazar las páginas que no se pueden descargar o decodificar por un aviso en lugar de fallar."
+        }
+        (Lang::En, "skip_broken_pages") => {
+            "Replace pages that can not be downloaded or decoded with a placeholder instead of failing."
+        }
+        (Lang::Es, "retries") => {
+            "Veces que se vuelve a descargar una página incompleta o corrupta."
+        }
+        (Lang::En, "retries") => {
+            "Times a page is downloaded again when it is incomplete or corrupted."
+        }
+        (Lang::Es, "interactive") => "Elegir los capítulos y su scan en una interfaz de terminal.",
+        (Lang::En, "interactive") => "Pick the chapters and their scan in a terminal interface.",
+        (Lang::Es, "quiet") => "No mostrar el progreso.",
+        (Lang::En, "quiet") => "Do not show progress.",
+        (Lang::Es, "verbose") => "Mostrar más mensajes de log, -v para info y -vv para debug.",
+        (Lang::En, "verbose") => "Show more log messages, -v for info and -vv for debug.",
+        (Lang::Es, "log_file") => "Escribir los mensajes de log en PATH en lugar de stderr.",
+        (Lang::En, "log_file") => "Write the log messages to PATH instead of stderr.",
+        (Lang::Es, "log_json") => "Escribir los mensajes de log como líneas JSON.",
+        (Lang::En, "log_json") => "Write the log messages as JSON lines.",
+        (Lang::Es, "mirror") => "Otro dominio de TMO del que aceptar urls. Se puede repetir.",
+        (Lang::En, "mirror") => "Another domain of TMO to accept urls from. Can be repeated.",
+        (Lang::Es, "base_domain") => {
+            "Dominio en el que está TMO, las urls de todos los espejos se reescriben a él."
+        }
+        (Lang::En, "base_domain") => {
+            "Domain TMO lives on, urls of every mirror are rewritten to it."
+        }
+        (Lang::Es, "lang") => "Idioma de los mensajes. Por defecto el de LANG.",
+        (Lang::En, "lang") => "Language of the messages. Defaults to the one of LANG.",
+        (Lang::Es, "query") => "Palabras a buscar",
+        (Lang::En, "query") => "Words to search",
+        (Lang::Es, "scan") => "Scan preferido cuando un capítulo tiene varios.",
+        (Lang::En, "scan") => "Scan preferred when a chapter has several.",
+        (Lang::Es, "index") => "ID de la serie que se deja de seguir",
+        (Lang::En, "index") => "ID of the serie to stop following",
+        (Lang::Es, "help") => "Mostrar la ayuda",
+        (Lang::En, "help") => "Print help",
+        (Lang::Es, "version") => "Mostrar la versión",
+        (Lang::En, "version") => "Print version",
+        _ => return None,
+    };
+    return Some(help);
+}
@@ -0,0 +1,40 @@
+//! Downloads chapters from TMO and exports them as PDF, CBZ or plain images.
+//!
+//! ```no_run
+//! use tmo_pdf_downloader::Client;
+//! use tmo_pdf_downloader::export::Metadata;
+//! use tmo_pdf_downloader::export::pdf::{PdfOptions, create_pdf};
+//! use tmo_pdf_downloader::models::serie::Serie;
+//! use tmo_pdf_downloader::sites::{SiteRegistry, SiteUrl};
+//! use std::fs::File;
+//! use std::path::Path;
+//!
+//! let url = "https://zonatmo.com/library/manga/12345/name";
+//! let (site, SiteUrl::Serie(url_info)) = SiteRegistry::default().parse_url(url).unwrap() else {
+//!     panic!("not a serie url");
+//! };
+//! let client = Client::new().with_site(site);
+//! let direction = url_info.reading_direction();
+//! let serie = Serie::fetch(&client, url_info).unwrap();
+//! let chapter = &serie.chapters[0];
+//! let pages = chapter.download(&client, Path::new("pages")).unwrap();
+//! let metadata = Metadata::new(&serie, chapter, &chapter.providers[0].scan);
+//! let file = File::create("chapter.pdf").unwrap();
+//! create_pdf(file, &metadata, &pages, direction, &PdfOptions::default()).unwrap();
+//! ```
+//!
+//! With the `async` feature the same steps are available without blocking,
+//! through `AsyncClient`, `Serie::fetch_async` and `Chapter::download_async`.
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod client;
+pub mod errors;
+pub mod export;
+pub mod i18n;
+pub mod models;
+pub mod processing;
+pub mod sites;
+
+#[cfg(feature = "async")]
+pub use async_client::AsyncClient;
+pub use client::Client;
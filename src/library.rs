@@ -0,0 +1,159 @@
+use crate::app::{App, RunError, Target, prefer_provider};
+use crate::picker::Pick;
+use clap::ValueEnum;
+use dirs::config_dir;
+use log::{error, info, warn};
+use std::fs;
+use std::path::PathBuf;
+use tmo_pdf_downloader::errors::LibraryError;
+use tmo_pdf_downloader::export::chapter::OutputFormat;
+use tmo_pdf_downloader::i18n::Msg;
+use tmo_pdf_downloader::models::library::{Library, LibraryEntry};
+
+// Names of series and chapters can contain path separators.
+fn file_name(name: &str) -> String {
+    return name.replace(['/', '\\'], "-");
+}
+
+fn get_library_path() -> PathBuf {
+    let config_path = config_dir().expect("No path to folder");
+    return config_path.join("tmo-pdf-downloader").join("library.json");
+}
+
+fn load_library() -> Result<Library, LibraryError> {
+    return Library::from_file(&get_library_path());
+}
+
+fn save_library(library: &Library) -> Result<(), LibraryError> {
+    return library.to_file(&get_library_path());
+}
+
+/// Follows the serie of the target. The chapters already released are
+/// marked as downloaded, so `update` only brings the next ones.
+pub fn add(
+    app: &App,
+    target: Target,
+    scan: Option<String>,
+    format: Option<OutputFormat>,
+    output: Option<PathBuf>,
+) -> Result<(), RunError> {
+    let (manga, _) = app.load_target(target)?;
+
+    let mut entry = LibraryEntry::new(manga.url_info.clone(), manga.title.clone());
+    entry.scan = scan;
+    entry.format = format
+        .and_then(|format| format.to_possible_value())
+        .map(|value| value.get_name().to_string());
+    entry.output = output;
+    for chapter in &manga.chapters {
+        entry.mark_downloaded(chapter);
+    }
+
+    let mut library = load_library()?;
+    library.add(entry);
+    save_library(&library)?;
+    info!(index = manga.url_info.index, title = manga.title.as_str(); "serie followed");
+    println!("{}: {}", manga.title, Msg::Following(manga.chapters.len()));
+    return Ok(());
+}
+
+pub fn remove(index: u32) -> Result<(), RunError> {
+    let mut library = load_library()?;
+    if !library.remove(index) {
+        println!("{}", Msg::NotFollowed(index));
+        return Ok(());
+    }
+    save_library(&library)?;
+    println!("{}", Msg::Unfollowed(index));
+    return Ok(());
+}
+
+/// Fetches every followed serie again and downloads the chapters that were
+/// not downloaded before, saving the library after each one. A serie or
+/// chapter that fails is reported and left for the next update.
+pub fn update(app: &App) -> Result<(), RunError> {
+    let mut library = load_library()?;
+    if library.entries.is_empty() {
+        println!("{}", Msg::LibraryEmpty);
+        return Ok(());
+    }
+
+    let mut failed: usize = 0;
+    for position in 0..library.entries.len() {
+        let entry = library.entries[position].clone();
+        let index = entry.url_info.index;
+        let mut manga = match app.fetch_serie(entry.url_info.clone()) {
+            Ok(manga) => manga,
+            Err(error) => {
+                error!(index, title = entry.title.as_str(), error:%; "could not fetch the serie");
+                failed += 1;
+                continue;
+            }
+        };
+        let new_chapters = entry.new_chapters(&manga);
+        println!("{}: {}", manga.title, Msg::NewChapters(new_chapters.len()));
+        if new_chapters.is_empty() {
+            continue;
+        }
+
+        for chapter in &new_chapters {
+            let provider = entry.provider(&manga.chapters[*chapter]);
+            prefer_provider(&mut manga.chapters[*chapter], provider);
+        }
+
+        let mut export = app.export.clone();
+        if let Some(name) = entry.format.as_deref() {
+            export.format = OutputFormat::from_str(name, true)
+                .inspect_err(|error| warn!(format = name, error:%; "unknown format in the library"))
+                .ok();
+        }
+        let folder = entry
+            .output
+            .clone()
+            .unwrap_or_default()
+            .join(file_name(&manga.title));
+        if let Err(error) = fs::create_dir_all(&folder) {
+            error!(index, path:% = folder.display(), error:%; "could not create the output folder");
+            failed += new_chapters.len();
+            continue;
+        }
+
+        let Some(site) = app.sites.by_name(&manga.url_info.site) else {
+            let site = manga.url_info.site.as_str();
+            error!(index, site; "the serie is from an unknown site");
+            failed += new_chapters.len();
+            continue;
+        };
+        let client = app.client_for(site);
+        let mut chapters_task = app
+            .progress
+            .bar(&Msg::ChaptersStep.to_string(), new_chapters.len() as u64);
+        for chapter in new_chapters {
+            let output = folder.join(file_name(&manga.chapters[chapter].name));
+            let pick = Pick {
+                chapter,
+                provider: 0,
+                image_urls: None,
+            };
+            let result = app.download_chapter(&export, &client, &manga, &pick, &output);
+            match result {
+                Ok(()) => {
+                    library.entries[position].mark_downloaded(&manga.chapters[chapter]);
+                    save_library(&library)?;
+                }
+                Err(error) => {
+                    let name = manga.chapters[chapter].name.as_str();
+                    error!(index, chapter = name, error:%; "could not download the chapter");
+                    failed += 1;
+                }
+            }
+            chapters_task.inc(0);
+        }
+        chapters_task.finish(&Msg::ChaptersDone.to_string());
+    }
+
+    if failed > 0 {
+        return Err(RunError::Failed(failed));
+    }
+    return Ok(());
+}
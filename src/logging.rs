@@ -0,0 +1,154 @@
+use chrono::Utc;
+use indicatif::MultiProgress;
+use log::kv::{self, Key, Value, VisitSource};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{Map, Value as JsonValue};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Logger that writes text or JSON lines to stderr or to a file.
+struct Logger {
+    level: LevelFilter,
+    json: bool,
+    file: Option<Mutex<File>>,
+    // Lines written to stderr hide the progress bars while they are printed.
+    bars: Option<MultiProgress>,
+}
+
+/// Sets the global logger. Verbosity 0 only shows warnings and errors,
+/// 1 adds info, 2 adds debug and 3 or more adds trace.
+pub fn init(
+    verbosity: u8,
+    log_file: Option<&Path>,
+    json: bool,
+    bars: Option<MultiProgress>,
+) -> Result<(), io::Error> {
+    let level = match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+
+    let file = match log_file {
+        Some(path) => Some(Mutex::new(
+            OpenOptions::new().create(true).append(true).open(path)?,
+        )),
+        None => None,
+    };
+
+    let logger = Logger {
+        level,
+        json,
+        file,
+        bars,
+    };
+
+    // Only fails if a logger was already set, which would be a bug.
+    log::set_boxed_logger(Box::new(logger)).expect("Logger is only set once.");
+    log::set_max_level(level);
+    return Ok(());
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Info and debug messages of dependencies, like reqwest, are only shown on trace.
+        if !metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+            && self.level < LevelFilter::Trace
+        {
+            return metadata.level() <= LevelFilter::Warn;
+        }
+        return metadata.level() <= self.level;
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = if self.json {
+            json_line(record)
+        } else {
+            text_line(record)
+        };
+
+        match &self.file {
+            Some(file) => {
+                if let Ok(mut file) = file.lock() {
+                    let _ = writeln!(file, "{}", line);
+                }
+            }
+            None => match &self.bars {
+                Some(bars) => bars.suspend(|| eprintln!("{}", line)),
+                None => eprintln!("{}", line),
+            },
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(Ok(mut file)) = self.file.as_ref().map(|file| file.lock()) {
+            let _ = file.flush();
+        }
+    }
+}
+
+fn text_line(record: &Record) -> String {
+    let mut fields = TextFields(String::new());
+    let _ = record.key_values().visit(&mut fields);
+
+    return format!(
+        "{} {:<5} {}: {}{}",
+        Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+        record.level(),
+        record.target(),
+        record.args(),
+        fields.0
+    );
+}
+
+fn json_line(record: &Record) -> String {
+    let mut fields = JsonFields(Map::new());
+    fields.0.insert(
+        "timestamp".to_string(),
+        JsonValue::from(Utc::now().to_rfc3339()),
+    );
+    fields.0.insert(
+        "level".to_string(),
+        JsonValue::from(record.level().as_str()),
+    );
+    fields
+        .0
+        .insert("target".to_string(), JsonValue::from(record.target()));
+    fields.0.insert(
+        "message".to_string(),
+        JsonValue::from(record.args().to_string()),
+    );
+    let _ = record.key_values().visit(&mut fields);
+
+    return JsonValue::Object(fields.0).to_string();
+}
+
+struct TextFields(String);
+
+impl<'kvs> VisitSource<'kvs> for TextFields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.push_str(&format!(" {}={}", key, value));
+        return Ok(());
+    }
+}
+
+struct JsonFields(Map<String, JsonValue>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let json = match (value.to_u64(), value.to_bool()) {
+            (Some(number), _) => JsonValue::from(number),
+            (_, Some(boolean)) => JsonValue::from(boolean),
+            _ => JsonValue::from(value.to_string()),
+        };
+        self.0.insert(key.to_string(), json);
+        return Ok(());
+    }
+}
@@ -1,55 +1,88 @@
-mod errors;
-mod models;
-mod utils;
-use models::pages::ChapterParser;
-use models::serie::{Serie, SerieUrlInfo};
-use printpdf::PdfSaveOptions;
+mod app;
+mod library;
+mod logging;
+mod picker;
+mod progress;
+mod search;
+use app::{App, RunError, Target};
+use progress::Progress;
+use tmo_pdf_downloader::errors::{ChapterError, ProcessingError};
+use tmo_pdf_downloader::export::chapter::{ChapterExport, OutputFormat};
+use tmo_pdf_downloader::export::pdf::WebpEmbedding;
+use tmo_pdf_downloader::i18n::{self, Lang, Msg, arg_help};
+use tmo_pdf_downloader::models::serie::ReadingDirection;
+use tmo_pdf_downloader::processing::convert::ConvertFormat;
+use tmo_pdf_downloader::processing::credits::CreditHashes;
+use tmo_pdf_downloader::processing::profile::DeviceProfile;
+use tmo_pdf_downloader::sites::tmo::Tmo;
+use tmo_pdf_downloader::sites::{SiteRegistry, SiteUrl};
 
-use utils::fetch;
-
-use clap::Parser;
 use clap::ValueEnum;
 use clap::error as ClapError;
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
 
-use dirs::cache_dir;
-use std::fs;
-use std::io::Write;
+use dirs::{cache_dir, config_dir};
+use log::{error, info};
+use std::env;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use std::time::Instant;
-use url::Url;
-
-fn px_to_mm(px: f32) -> f32 {
-    // mm = ( pixels * 25.4 ) / DPI
-    return px * 25.4 / 300.0;
-}
 
 fn get_cache_path() -> PathBuf {
     let cache_path = cache_dir().expect("No path to folder");
     cache_path.join("tmo-pdf-downloader")
 }
 
+fn get_credit_hashes_path() -> PathBuf {
+    let config_path = config_dir().expect("No path to folder");
+    config_path
+        .join("tmo-pdf-downloader")
+        .join("credit-hashes.json")
+}
+
+// Ratios must be finite and positive, zero would cut one page per row.
+fn parse_ratio(s: &str) -> Result<f32, String> {
+    let ratio: f32 = s.parse().map_err(|_| Msg::InvalidRatio.to_string())?;
+    if !ratio.is_finite() || ratio <= 0.0 {
+        return Err(Msg::InvalidRatio.to_string());
+    }
+    return Ok(ratio);
+}
+
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    disable_help_flag = true,
+    disable_version_flag = true,
+    disable_help_subcommand = true,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
 struct Args {
-    /// ID del manga o manwha a descargar
-    #[arg(required = true, value_parser = parse_url )]
-    id: (u32, Option<SerieUrlInfo>),
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// ID or URL of the manga or manhwa to download
+    #[arg(required = true)]
+    id: Option<String>,
 
     // El grupo de argumentos para la selección de capítulos
-    /// Número de capítulo a descargar
+    /// Number of the chapter to download
     #[arg(short, long, group = "selection")]
     chapter: Option<u32>,
 
-    /// Rango de capítulos a descargar (ej: 10-15, 25-end)
+    /// Range of chapters to download (e.g. 10-15, 25-end)
     #[arg(short, long, group = "selection")]
     range: Option<String>,
 
-    /// Descargar todos los capítulos disponibles
+    /// Download every available chapter
     #[arg(short, long, group = "selection")]
     all: bool,
 
-    /// Descargar solo el último capítulo disponible (por defecto si no se especifica nada)
+    /// Download only the last available chapter (default)
     #[arg(
         short,
         long,
@@ -59,213 +92,391 @@ struct Args {
     )]
     last: bool,
 
-    /// Ruta de salida para los archivos descargados
+    /// Pick the chapters and their scan in a terminal interface.
+    #[arg(short, long, group = "selection")]
+    interactive: bool,
+
+    /// Output path of the downloaded files
     #[arg(short, long, value_name = "PATH")]
     output: Option<String>,
 
-    /// Output file.
-    #[arg(value_enum, long, default_value_t = FormatOutput::Pdf)]
-    format: FormatOutput,
+    /// Output format. Defaults to pdf, or to the best one for the selected profile.
+    #[arg(value_enum, long)]
+    format: Option<OutputFormat>,
 
     /// Avoid reading or writing to cache.
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_cache: bool,
+
+    /// Split images taller than RATIO (height / width) into page-sized chunks.
+    #[arg(
+        long,
+        value_name = "RATIO",
+        num_args = 0..=1,
+        default_missing_value = "1.294",
+        value_parser = parse_ratio,
+        global = true
+    )]
+    split_tall: Option<f32>,
+
+    /// Stitch consecutive images into continuous strips up to HEIGHT pixels tall.
+    #[arg(
+        long,
+        value_name = "HEIGHT",
+        num_args = 0..=1,
+        default_missing_value = "10000",
+        conflicts_with = "split_tall"
+    , global = true)]
+    stitch: Option<u32>,
+
+    /// Split landscape double-page spreads into two pages following the reading direction.
+    #[arg(long, global = true)]
+    split_spreads: bool,
+
+    /// Reading direction of the output. Defaults to rtl for manga and ltr for manhwa.
+    #[arg(value_enum, long, global = true)]
+    direction: Option<ReadingDirection>,
+
+    /// Convert the images to another format.
+    #[arg(value_enum, long, global = true)]
+    convert: Option<ConvertFormat>,
+
+    /// Quality used when converting to JPEG (1-100).
+    #[arg(long, default_value_t = 85, value_parser = clap::value_parser!(u8).range(1..=100), global = true)]
+    quality: u8,
+
+    /// Downscale images wider than WIDTH pixels.
+    #[arg(long, value_name = "WIDTH", global = true)]
+    max_width: Option<u32>,
+
+    /// Optimize the images for a reading device.
+    #[arg(value_enum, long, global = true)]
+    profile: Option<DeviceProfile>,
+
+    /// Crop uniform white or black borders of the pages.
+    #[arg(long, global = true)]
+    trim: bool,
+
+    /// Maximum luma difference (0-255) of a pixel to be considered part of the border.
+    #[arg(long, default_value_t = 16, requires = "trim", global = true)]
+    trim_tolerance: u8,
+
+    /// Maximum fraction (0-1) of each side that can be trimmed.
+    #[arg(long, default_value_t = 0.15, requires = "trim", global = true)]
+    trim_limit: f32,
+
+    /// Drop credit and recruitment pages matching the known hashes of the scan.
+    #[arg(long, global = true)]
+    drop_credits: bool,
+
+    /// JSON file with the credit page hashes of every scan.
+    #[arg(long, value_name = "PATH", global = true)]
+    credit_hashes: Option<PathBuf>,
+
+    /// Print the perceptual hash of every page, to register new credit pages.
+    #[arg(long, global = true)]
+    show_hashes: bool,
+
+    /// Prepend the series cover as the first page.
+    #[arg(long, global = true)]
+    cover: bool,
+
+    /// How WebP pages are embedded in the PDF. Jpeg uses the quality setting.
+    #[arg(value_enum, long, default_value_t = WebpEmbedding::Flate, global = true)]
+    pdf_webp: WebpEmbedding,
+
+    /// Replace pages that can not be downloaded or decoded with a placeholder instead of failing.
+    #[arg(long, global = true)]
+    skip_broken_pages: bool,
+
+    /// Times a page is downloaded again when it is incomplete or corrupted.
+    #[arg(long, default_value_t = 3, global = true)]
+    retries: u32,
+
+    /// Do not show progress.
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Show more log messages, -v for info and -vv for debug.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Write the log messages to PATH instead of stderr.
+    #[arg(long, value_name = "PATH", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Write the log messages as JSON lines.
+    #[arg(long, global = true)]
+    log_json: bool,
+
+    /// Another domain of TMO to accept urls from. Can be repeated.
+    #[arg(long, value_name = "DOMAIN", global = true)]
+    mirror: Vec<String>,
+
+    /// Domain TMO lives on, urls of every mirror are rewritten to it.
+    #[arg(long, value_name = "DOMAIN", global = true)]
+    base_domain: Option<String>,
+
+    /// Language of the messages. Defaults to the one of LANG.
+    #[arg(value_enum, long, global = true)]
+    lang: Option<Lang>,
+
+    /// Print help
+    #[arg(short, long, action = ArgAction::Help)]
+    help: Option<bool>,
+
+    /// Print version
+    #[arg(short = 'V', long, action = ArgAction::Version)]
+    version: Option<bool>,
 }
 
-#[derive(Clone, ValueEnum)]
-enum FormatOutput {
-    Pdf,
-    Images,
+#[derive(Subcommand)]
+enum Command {
+    /// Search a serie by its title and pick one to list or download.
+    #[command(disable_help_flag = true)]
+    Search {
+        /// Words to search
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+
+        /// Print help
+        #[arg(short, long, action = ArgAction::Help)]
+        help: Option<bool>,
+    },
+
+    /// Follow a serie, `update` downloads the chapters released after this.
+    #[command(disable_help_flag = true)]
+    Add {
+        /// ID or URL of the manga or manhwa to download
+        id: String,
+
+        /// Scan preferred when a chapter has several.
+        #[arg(long)]
+        scan: Option<String>,
+
+        /// Output format. Defaults to pdf, or to the best one for the selected profile.
+        #[arg(value_enum, long)]
+        format: Option<OutputFormat>,
+
+        /// Output path of the downloaded files
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Print help
+        #[arg(short, long, action = ArgAction::Help)]
+        help: Option<bool>,
+    },
+
+    /// Stop following a serie.
+    #[command(disable_help_flag = true)]
+    Remove {
+        /// ID of the serie to stop following
+        index: u32,
+
+        /// Print help
+        #[arg(short, long, action = ArgAction::Help)]
+        help: Option<bool>,
+    },
+
+    /// Download the new chapters of every followed serie.
+    #[command(disable_help_flag = true)]
+    Update {
+        /// Print help
+        #[arg(short, long, action = ArgAction::Help)]
+        help: Option<bool>,
+    },
 }
 
-fn parse_url(s: &str) -> Result<(u32, Option<SerieUrlInfo>), ClapError::Error> {
+// Extracts the --lang value before parsing, it is needed to translate the help.
+fn lang_from_args() -> Option<Lang> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--lang") {
+            Some("") => args.next()?,
+            Some(rest) => rest.strip_prefix('=')?.to_string(),
+            None => continue,
+        };
+        return Lang::from_str(&value, true).ok();
+    }
+    return None;
+}
+
+/// Command line with the help in the current language.
+fn localized_command() -> clap::Command {
+    let command = localize_args(Args::command().about(Msg::About.to_string()))
+        .subcommand_help_heading(Msg::CommandsHeading.to_string());
+    let subcommands = [
+        ("search", Msg::SearchAbout),
+        ("add", Msg::AddAbout),
+        ("remove", Msg::RemoveAbout),
+        ("update", Msg::UpdateAbout),
+    ];
+    return subcommands
+        .into_iter()
+        .fold(command, |command, (name, about)| {
+            command.mut_subcommand(name, |sub| localize_args(sub.about(about.to_string())))
+        });
+}
+
+fn localize_args(command: clap::Command) -> clap::Command {
+    let command = command.help_template(format!(
+        "{{about-with-newline}}\n{} {{usage}}\n\n{{all-args}}{{after-help}}",
+        Msg::UsageHeading
+    ));
+
+    let ids: Vec<String> = command
+        .get_arguments()
+        .map(|arg| arg.get_id().to_string())
+        .collect();
+
+    return ids.iter().fold(command, |command, id| {
+        command.mut_arg(id, |arg| {
+            let heading = if arg.is_positional() {
+                Msg::ArgumentsHeading
+            } else {
+                Msg::OptionsHeading
+            };
+            let arg = arg.help_heading(heading.to_string());
+            match arg_help(id) {
+                Some(help) => arg.help(help),
+                None => arg,
+            }
+        })
+    });
+}
+
+// Sites known by the command line, with the domains given in the arguments.
+fn site_registry(args: &Args) -> SiteRegistry {
+    let mut tmo = args
+        .mirror
+        .iter()
+        .fold(Tmo::default(), |tmo, domain| tmo.with_mirror(domain));
+    if let Some(domain) = &args.base_domain {
+        tmo = tmo.with_base_domain(domain);
+    }
+
+    let mut sites = SiteRegistry::empty();
+    sites.register(Arc::new(tmo));
+    return sites;
+}
+
+fn parse_url(sites: &SiteRegistry, s: &str) -> Result<Target, ClapError::Error> {
     // Try to parse as a numeric ID.
     if let Ok(index) = s.parse::<u32>() {
-        return Ok((index, None));
+        return Ok(Target::Id(index));
     };
 
     // If it's not a number, try to parse as a URL.
-    let url = match Url::parse(s) {
-        Ok(u) => u,
-        Err(_) => {
-            return Err(ClapError::Error::raw(
-                ClapError::ErrorKind::InvalidValue,
-                "The value must be a numeric ID or a valid URL.",
-            ));
-        }
-    };
-
-    // Validate the URL host
-    if url.host_str() != Some("zonatmo.com") {
-        return Err(ClapError::Error::raw(
+    let (site, url) = sites.parse_url(s).map_err(|error| {
+        localized_command().error(ClapError::ErrorKind::InvalidValue, error.to_string())
+    })?;
+
+    return match url {
+        SiteUrl::Serie(partial) => Ok(Target::Serie(partial)),
+        SiteUrl::Reader(reader) => Ok(Target::Reader(site, reader)),
+        _ => Err(localized_command().error(
             ClapError::ErrorKind::InvalidValue,
-            "The URL is not from zonatmo.com.",
-        ));
+            Msg::UnsupportedUrl.to_string(),
+        )),
     };
+}
 
-    // Get the path segments
-    let segments: Vec<&str> = match &url.path_segments() {
-        Some(s) => s.clone().collect(),
-        None => {
-            return Err(ClapError::Error::raw(
-                ClapError::ErrorKind::InvalidValue,
-                "The TMO URL must have a path with segments.",
-            ));
-        }
-    };
+// Maps the options of the command line onto the steps run on every chapter.
+fn chapter_export(args: &Args) -> Result<ChapterExport, ProcessingError> {
+    let mut export = ChapterExport::new(get_cache_path());
+    export.format = args.format;
+    export.direction = args.direction;
+    export.skip_broken_pages = args.skip_broken_pages;
+    export.hash_pages = args.show_hashes;
+    if args.drop_credits {
+        let path = args
+            .credit_hashes
+            .clone()
+            .unwrap_or(get_credit_hashes_path());
+        export.credits = Some(CreditHashes::from_file(&path)?);
+    }
+    export.trim = args.trim;
+    export.trim_tolerance = args.trim_tolerance;
+    export.trim_limit = args.trim_limit;
+    export.split_spreads = args.split_spreads;
+    export.split_tall = args.split_tall;
+    export.stitch = args.stitch;
+    export.convert.format = args.convert;
+    export.convert.quality = args.quality;
+    export.convert.max_width = args.max_width;
+    export.cover = args.cover;
+    export.profile = args.profile;
+    export.pdf.webp = args.pdf_webp;
+    export.pdf.quality = args.quality;
+    return Ok(export);
+}
 
-    // Validate the path structure and extract the ID
-    if segments.len() < 4 || segments[0] != "library" {
-        return Err(ClapError::Error::raw(
-            ClapError::ErrorKind::InvalidValue,
-            "Invalid URL format. It should be similar to https://zonatmo.com/library/manga/12345/name.",
-        ));
+// Runs the command of the arguments.
+fn run(args: &Args, progress: Progress) -> Result<(), RunError> {
+    let export = chapter_export(args).map_err(ChapterError::from)?;
+    let sites = site_registry(args);
+    let app = App {
+        sites,
+        progress,
+        export,
+        cache_folder: get_cache_path(),
+        no_cache: args.no_cache,
+        retries: args.retries,
+        interactive: args.interactive,
+        output: args.output.clone(),
     };
 
-    let index = match segments[2].parse::<u32>() {
-        Ok(res) => res,
-        Err(_) => {
-            return Err(ClapError::Error::raw(
-                ClapError::ErrorKind::InvalidValue,
-                "The URL does not contain a valid numeric manga ID.",
-            ));
+    return match &args.command {
+        Some(Command::Search { query, .. }) => search::run(&app, &query.join(" ")),
+        Some(Command::Add {
+            id,
+            scan,
+            format,
+            output,
+            ..
+        }) => {
+            let target = parse_url(&app.sites, id).unwrap_or_else(|error| error.exit());
+            library::add(&app, target, scan.clone(), *format, output.clone())
+        }
+        Some(Command::Remove { index, .. }) => library::remove(*index),
+        Some(Command::Update { .. }) => library::update(&app),
+        None => {
+            let id = args
+                .id
+                .as_deref()
+                .expect("The id is required without a command.");
+            let target = parse_url(&app.sites, id).unwrap_or_else(|error| error.exit());
+            app.download(target)
         }
     };
-
-    let is_oneshot = segments[1] == "one_shot";
-
-    let slug = segments[3].to_string();
-
-    let partial = SerieUrlInfo {
-        url: url.to_string(),
-        index,
-        slug,
-        is_oneshot,
-    };
-
-    return Ok((index, Some(partial)));
 }
 
 fn main() {
     let program_time = Instant::now();
     // 1
-    let args = Args::parse();
-
-    let cache_path = get_cache_path();
-
-    if args.no_cache && args.id.1.is_none() {
-        panic!("we cant get from cache and we cant fecth due to we have not the url.");
-    }
-
-    let manga: Serie = match Serie::from_cache(&cache_path, &args.id.0.to_string()) {
-        Ok(r) => r,
-        Err(_) => {
-            // we can get from cache
-
-            let Some(part) = args.id.1 else {
-                panic!("we cant get from cache and we cant fecth due to we have not the url.");
-            };
-
-            let index = args.id.0;
-
-            println!("Caché no encontrada. Haciendo fetch de los datos.");
-            let response = fetch(&part.url.to_string()).expect("Error on fecth");
-            let html_file = response.text().expect("Incorrect body");
-            let manga = Serie::from_html(part, &html_file).expect("error on parsing");
-
-            if !args.no_cache {
-                let _ = manga.to_cache(&cache_path, &index.to_string());
-            }
-
-            manga
+    i18n::set_lang(lang_from_args().unwrap_or_else(Lang::from_env));
+    let matches = localized_command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    let progress = Progress::new(args.quiet);
+    logging::init(
+        args.verbose,
+        args.log_file.as_deref(),
+        args.log_json,
+        progress.bars(),
+    )
+    .expect("error on opening the log file");
+
+    let result = run(&args, progress);
+    info!(seconds = program_time.elapsed().as_secs(); "finished");
+
+    if let Err(error) = result {
+        // The log line is kept for log files and JSON output, people read the message.
+        if args.log_file.is_some() || args.log_json {
+            error!(error:%; "the command failed");
         }
-    };
-
-    // // as no chapter still, we'll take first
-    // // this can fail if one_shot
-    // let chap_index: usize = match args.chapter_index {
-    //     Some(res) => res as usize,
-    //     None => 0,
-    // };
-
-    let chap_index: usize = 0;
-    println!("chapter selected had the index {}", chap_index);
-
-    let url_chap_view = &manga.chapters[chap_index].providers[0].link;
-    let chap_name = &manga.chapters[chap_index].name;
-    let ss = fetch(&url_chap_view).unwrap().text().unwrap();
-    let _ = fs::write("test.html", &ss);
-    let urss = ChapterParser::get_images(&ss).unwrap();
-    println!("we got the urls for the chapter");
-
-    // Itera sobre las url y trata de descargar las imagenes.
-    let folder_chapter = cache_path.join(manga.url_info.index.to_string()).join(chap_name);
-    let mut images_path: Vec<PathBuf> = Vec::new();
-    let _ = fs::create_dir_all(folder_chapter.clone());
-    println!("we'll start fetching images");
-    let fetching_time = Instant::now();
-    for (i, image_url) in urss.iter().enumerate() {
-        let response = fetch(&image_url.as_str().to_string()).expect("error no fetch");
-        let bytes = response.bytes().expect("error on parsing images bytes");
-        let path = folder_chapter.join(format!("{}.webp", i));
-        fs::write(&path, bytes).expect("no write");
-        println!("image {} saved on disk.", i);
-        images_path.push(path);
-    }
-    println!(
-        "all the fetch ellpased {} seconds",
-        fetching_time.elapsed().as_secs()
-    );
-
-    // 4
-    // crea el pdf
-    println!("we got all the images, now the pdf");
-    let mut ub_final = File::create("test.pdf").expect("create file");
-    create_pdf(&mut ub_final, &chap_name, &images_path);
-    println!("finish in {} seconds", program_time.elapsed().as_secs());
-}
-
-use printpdf::{Mm, Op, PdfDocument, PdfPage, PdfWarnMsg, Pt, RawImage, XObjectTransform};
-use std::fs::File;
-fn create_pdf(file: &mut File, name: &str, paths: &Vec<PathBuf>) {
-    let mut warnings: Vec<PdfWarnMsg> = Vec::new();
-    let mut document = PdfDocument::new(name);
-
-    let mut pages: Vec<PdfPage> = Vec::new();
-    for image_path in paths {
-        let letter_dimension = (8.5 * 25.4, 11.0 * 25.4);
-
-        let image_bytes = fs::read(image_path).expect("image not found");
-        let image = RawImage::decode_from_bytes(&image_bytes, &mut warnings).expect("decode");
-        let image_ratio = (image.height / image.width) as f32;
-
-        let image_xobject_ref = document.add_image(&image);
-        let page_content = vec![Op::UseXobject {
-            id: image_xobject_ref,
-            transform: XObjectTransform {
-                translate_x: Some(Pt(0.0)),
-                translate_y: Some(Pt(0.0)),
-                scale_x: Some(letter_dimension.0 / px_to_mm(image.width as f32)),
-                scale_y: Some(image_ratio * letter_dimension.0 / px_to_mm(image.height as f32)),
-                dpi: None,
-                rotate: None,
-            }, // transform to fit in a letter page.
-        }];
-
-        // let width = Mm(px_to_mm(image.width as f32));
-        // let heigth = Mm(px_to_mm(image.height as f32));
-        let page = PdfPage::new(
-            Mm(letter_dimension.0),
-            Mm(image_ratio * letter_dimension.0),
-            page_content,
-        ); // letter page;
-        pages.push(page);
+        if args.log_file.is_some() || !args.log_json {
+            eprintln!("{}: {}", Msg::CommandFailed, error);
+        }
+        std::process::exit(1);
     }
-
-    let pdf_bytes = document
-        .with_pages(pages)
-        .save(&PdfSaveOptions::default(), &mut warnings);
-
-    println!("we'll start to write");
-    file.write(&pdf_bytes).expect("good write");
 }
@@ -0,0 +1,201 @@
+use crate::errors::LibraryError;
+use crate::models::serie::{Chapter, Serie, SerieUrlInfo};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Series followed by the user, stored as a JSON file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Library {
+    pub entries: Vec<LibraryEntry>,
+}
+
+/// A followed serie with the settings used to download its new chapters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct LibraryEntry {
+    pub url_info: SerieUrlInfo,
+    pub title: String,
+    // Scan preferred over the others when a chapter has several.
+    #[serde(default)]
+    pub scan: Option<String>,
+    // Output format by name, as accepted by `--format`.
+    #[serde(default)]
+    pub format: Option<String>,
+    // Folder where the chapters are written.
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+    #[serde(default)]
+    pub downloaded: Vec<(u32, u32)>,
+}
+
+impl Library {
+    /// Loads the library. A missing file is treated as an empty library.
+    pub fn from_file(path: &Path) -> Result<Library, LibraryError> {
+        if !path.is_file() {
+            return Ok(Library::default());
+        }
+
+        let contents = fs::read_to_string(path)?;
+        let library = serde_json::from_str(&contents)?;
+        return Ok(library);
+    }
+
+    pub fn to_file(&self, path: &Path) -> Result<(), LibraryError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self)?;
+        fs::write(path, json)?;
+        debug!(path:% = path.display(), entries = self.entries.len(); "library written");
+        return Ok(());
+    }
+
+    /// Adds the entry, replacing the one of the same serie.
+    pub fn add(&mut self, entry: LibraryEntry) {
+        match self.get_mut(entry.url_info.index) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Removes the serie, returns false when it was not in the library.
+    pub fn remove(&mut self, index: u32) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.url_info.index != index);
+        return self.entries.len() != before;
+    }
+
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut LibraryEntry> {
+        return self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.url_info.index == index);
+    }
+}
+
+impl LibraryEntry {
+    pub fn new(url_info: SerieUrlInfo, title: String) -> LibraryEntry {
+        return LibraryEntry {
+            url_info,
+            title,
+            scan: None,
+            format: None,
+            output: None,
+            downloaded: Vec::new(),
+        };
+    }
+
+    /// Positions of the chapters of the serie not downloaded yet, oldest first.
+    pub fn new_chapters(&self, serie: &Serie) -> Vec<usize> {
+        let mut positions: Vec<usize> = serie
+            .chapters
+            .iter()
+            .enumerate()
+            .filter(|(_, chapter)| !self.downloaded.contains(&chapter.number))
+            .map(|(i, _)| i)
+            .collect();
+        positions.sort_by_key(|i| serie.chapters[*i].number);
+        return positions;
+    }
+
+    /// Position of the provider of the preferred scan, the first one otherwise.
+    pub fn provider(&self, chapter: &Chapter) -> usize {
+        let Some(scan) = &self.scan else {
+            return 0;
+        };
+        return chapter
+            .providers
+            .iter()
+            .position(|provider| provider.scan.eq_ignore_ascii_case(scan))
+            .unwrap_or(0);
+    }
+
+    /// Marks the chapter as downloaded.
+    pub fn mark_downloaded(&mut self, chapter: &Chapter) {
+        if !self.downloaded.contains(&chapter.number) {
+            self.downloaded.push(chapter.number);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::serie::Provider;
+
+    fn url_info(index: u32) -> SerieUrlInfo {
+        return SerieUrlInfo::new(
+            format!("https://example.com/library/manga/{}/serie", index),
+            index,
+            "serie".to_string(),
+            false,
+            "manga".to_string(),
+            "tmo".to_string(),
+        );
+    }
+
+    fn chapter(number: (u32, u32), scans: &[&str]) -> Chapter {
+        let providers = scans
+            .iter()
+            .map(|scan| Provider::new(scan.to_string(), format!("https://example.com/{}", scan)))
+            .collect();
+        return Chapter::new(number, format!("{}.{}", number.0, number.1), providers);
+    }
+
+    #[test]
+    fn new_chapters_skips_downloaded_oldest_first() {
+        // Chapters are listed in any order by the site.
+        let serie = Serie::new(
+            url_info(1),
+            "Serie".to_string(),
+            None,
+            vec![
+                chapter((3, 0), &["a"]),
+                chapter((1, 0), &["a"]),
+                chapter((2, 5), &["a"]),
+            ],
+        );
+        let mut entry = LibraryEntry::new(url_info(1), "Serie".to_string());
+        assert_eq!(entry.new_chapters(&serie), vec![1, 2, 0]);
+
+        entry.mark_downloaded(&serie.chapters[1]);
+        assert_eq!(entry.new_chapters(&serie), vec![2, 0]);
+    }
+
+    #[test]
+    fn provider_prefers_scan_ignoring_case() {
+        let chapter = chapter((1, 0), &["First", "Second"]);
+        let mut entry = LibraryEntry::new(url_info(1), "Serie".to_string());
+        assert_eq!(entry.provider(&chapter), 0);
+
+        entry.scan = Some("second".to_string());
+        assert_eq!(entry.provider(&chapter), 1);
+
+        entry.scan = Some("Missing".to_string());
+        assert_eq!(entry.provider(&chapter), 0);
+    }
+
+    #[test]
+    fn add_replaces_same_serie() {
+        let mut library = Library::default();
+        library.add(LibraryEntry::new(url_info(1), "Old".to_string()));
+        library.add(LibraryEntry::new(url_info(2), "Other".to_string()));
+        library.add(LibraryEntry::new(url_info(1), "New".to_string()));
+
+        assert_eq!(library.entries.len(), 2);
+        assert_eq!(library.entries[0].title, "New");
+    }
+
+    #[test]
+    fn remove_reports_missing_serie() {
+        let mut library = Library::default();
+        library.add(LibraryEntry::new(url_info(1), "Serie".to_string()));
+
+        assert!(!library.remove(2));
+        assert!(library.remove(1));
+        assert!(library.entries.is_empty());
+    }
+}
@@ -1,2 +1,3 @@
-pub mod pages;
+pub mod library;
+pub mod search;
 pub mod serie;
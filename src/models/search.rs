@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// A serie found by the search of a site.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SearchResult {
+    pub title: String,
+    // Library type as shown by the site (manga, manhwa, novel...).
+    pub kind: String,
+    pub index: u32,
+    pub url: String,
+    pub score: Option<f32>,
+}
+
+impl SearchResult {
+    pub fn new(
+        title: String,
+        kind: String,
+        index: u32,
+        url: String,
+        score: Option<f32>,
+    ) -> SearchResult {
+        return SearchResult {
+            title,
+            kind,
+            index,
+            url,
+            score,
+        };
+    }
+}
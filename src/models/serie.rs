@@ -1,129 +1,125 @@
-use crate::errors::{CacheError, SerieParseError};
+#[cfg(feature = "async")]
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::errors::{CacheError, DownloadError, FetchError};
+use crate::processing::Page;
+use crate::sites::SiteAdapter;
 use chrono::Utc;
-use regex::Regex;
-use scraper::{ElementRef, Html, Selector};
+use log::debug;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use url::Url;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct SerieUrlInfo {
     pub url: String,
     pub index: u32,
     pub slug: String,
     pub is_oneshot: bool,
+    // Library type segment of the url (manga, manhwa, manhua, one_shot...).
+    #[serde(default)]
+    pub kind: String,
+    // Name of the site adapter that parsed the url.
+    #[serde(default = "default_site")]
+    pub site: String,
+}
+
+// Series cached before there were site adapters are all from TMO.
+fn default_site() -> String {
+    return "tmo".to_string();
+}
+
+impl SerieUrlInfo {
+    pub fn new(
+        url: String,
+        index: u32,
+        slug: String,
+        is_oneshot: bool,
+        kind: String,
+        site: String,
+    ) -> SerieUrlInfo {
+        return SerieUrlInfo {
+            url,
+            index,
+            slug,
+            is_oneshot,
+            kind,
+            site,
+        };
+    }
+
+    /// Rewrites the url to the current domain of the site, urls from another
+    /// site or that it does not recognize are kept.
+    pub fn rehost(&mut self, site: &dyn SiteAdapter) {
+        if let Ok(url) = Url::parse(&self.url)
+            && let Ok(url_info) = site.parse_url(&url)
+        {
+            self.url = url_info.url;
+        }
+    }
+
+    /// Manhwa and manhua are read left to right, everything else right to left.
+    /// Caches written by older versions have no type, fetch those again first.
+    pub fn reading_direction(&self) -> ReadingDirection {
+        match self.kind.as_str() {
+            "manhwa" | "manhua" | "oel" => ReadingDirection::Ltr,
+            _ => ReadingDirection::Rtl,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ReadingDirection {
+    /// Left to right.
+    Ltr,
+    /// Right to left.
+    Rtl,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Serie {
     // This info is extracted from the url.
     pub url_info: SerieUrlInfo,
     // This info is extracted from the html file.
     pub title: String,
+    #[serde(default)]
+    pub cover_url: Option<String>,
     pub chapters: Vec<Chapter>,
 }
 
-
 impl Serie {
-    /// Extracts info from the url and body of a manga html page.
-    pub fn from_html(url_info: SerieUrlInfo, html: &str) -> Result<Serie, SerieParseError> {
-        let document = Html::parse_document(&html);
-
-        // Extracts title from the body.
-        let title_selector = Selector::parse("h1.element-title").expect("Selector is hardcoded.");
-        let Some(title_node) = document.select(&title_selector).next() else {
-            return Err(SerieParseError::MissingTitle);
-        };
-        let title = match title_node.text().next() {
-            Some(text) => text.trim().to_string(),
-            None => return Err(SerieParseError::MissingTitle),
-        };
-
-        // If the serie is a one_shot we must extract providers in a different way.
-        if url_info.is_oneshot {
-            let providers_selector =
-                Selector::parse("li.list-group-item").expect("Selector is hardcoded.");
-            let provider_nodes = document.select(&providers_selector);
-
-            let providers: Vec<Provider> = provider_nodes
-                .filter_map(|j| Provider::from_oneshot_provider_fragment(j).ok())
-                .collect();
-
-            let chapters = vec![Chapter {
-                number: (0, 0),
-                name: title.clone(),
-                providers,
-            }];
-
-            return Ok(Serie {
-                url_info,
-                title,
-                chapters,
-            });
-        }
-
-        // Extracts chapters from the body.
-        let chapters_selector =
-            Selector::parse("div#chapters li.upload-link").expect("Selector is hardcoded.");
-        let chapters_nodes = document.select(&chapters_selector);
-        let mut chapters: Vec<Chapter> = Vec::new();
-
-        for chapter_node in chapters_nodes.rev() {
-            // Extracts chapter's title.
-            let name_selector = Selector::parse("h4 a").expect("Selector is hardcoded.");
-            let Some(name_node) = chapter_node.select(&name_selector).next() else {
-                return Err(SerieParseError::MissingTitle);
-            };
-            let name = match name_node.text().next() {
-                Some(text) => text.trim().to_string(),
-                None => return Err(SerieParseError::MissingTitle),
-            };
-
-            // Parses title to extract number
-            let regex = Regex::new(r"Capítulo\s+(\d+)\.(\d+)").expect("Regex is hardcoded.");
-            let Some(numbers) = regex.captures(&name) else {
-                return Err(SerieParseError::MissingTitle);
-            };
-            let Ok(complete_part) = numbers
-                .get(1)
-                .expect("It selects the first capture group.")
-                .as_str()
-                .parse::<u32>()
-            else {
-                return Err(SerieParseError::MissingTitle);
-            };
-            let Ok(decimal_part) = numbers
-                .get(2)
-                .expect("It selects the second capture group.")
-                .as_str()
-                .parse::<u32>()
-            else {
-                return Err(SerieParseError::MissingTitle);
-            };
-
-            let number = (complete_part, decimal_part);
-
-            // Extracts providers of the chapter.
-            let providers_selector =
-                Selector::parse("li.list-group-item").expect("Selector is hardcoded.");
-            let providers_nodes = chapter_node.select(&providers_selector);
-
-            let providers: Vec<Provider> = providers_nodes
-                .filter_map(|j| Provider::from_serie_provider_fragment(j).ok())
-                .collect();
-
-            chapters.push(Chapter {
-                number,
-                name,
-                providers,
-            });
-        }
-
-        return Ok(Serie {
+    pub fn new(
+        url_info: SerieUrlInfo,
+        title: String,
+        cover_url: Option<String>,
+        chapters: Vec<Chapter>,
+    ) -> Serie {
+        return Serie {
             url_info,
             title,
+            cover_url,
             chapters,
-        });
+        };
+    }
+
+    /// Downloads and parses the page of a serie with the site of the client.
+    pub fn fetch(client: &Client, url_info: SerieUrlInfo) -> Result<Serie, FetchError> {
+        let html = client.fetch(&url_info.url)?;
+        return Ok(client.site().parse_serie(url_info, &html)?);
+    }
+
+    /// Async version of `Serie::fetch`.
+    #[cfg(feature = "async")]
+    pub async fn fetch_async(
+        client: &AsyncClient,
+        url_info: SerieUrlInfo,
+    ) -> Result<Serie, FetchError> {
+        let html = client.fetch(&url_info.url).await?;
+        return Ok(client.site().parse_serie(url_info, &html)?);
     }
 
     pub fn from_cache(cache: &Path, index: &str) -> Result<Serie, CacheError> {
@@ -167,86 +163,130 @@ impl Serie {
             }
         }
 
-        let latest_file_path = latest_file_path.ok_or(CacheError::CacheNotFound)?;
+        let Some(latest_file_path) = latest_file_path else {
+            debug!(index; "no cache entry found");
+            return Err(CacheError::CacheNotFound);
+        };
 
         if current_timestamp.abs_diff(latest_timestamp) > fifteen_days_in_seconds {
+            debug!(index, path:% = latest_file_path.display(); "cache entry expired");
             return Err(CacheError::CacheExpired);
         };
 
+        debug!(index, path:% = latest_file_path.display(); "reading serie from cache");
         let contents = fs::read_to_string(latest_file_path)?;
         let json = serde_json::from_str(&contents)?;
         return Ok(json);
     }
 
+    /// Rewrites the urls of the serie and its chapters to the current domain of
+    /// the site, a cached serie keeps the domain it was fetched from.
+    pub fn rehost(&mut self, site: &dyn SiteAdapter) {
+        self.url_info.rehost(site);
+
+        let providers = self
+            .chapters
+            .iter_mut()
+            .flat_map(|chapter| chapter.providers.iter_mut());
+        for provider in providers {
+            if let Ok(url) = Url::parse(&provider.link)
+                && let Some(reader) = site.reader_url(&url)
+            {
+                provider.link = reader.to_string();
+            }
+        }
+    }
+
     pub fn to_cache(&self, cache: &Path, index: &str) -> Result<(), CacheError> {
-        fs::create_dir_all(&cache)?;
+        fs::create_dir_all(cache)?;
         let current_timestamp = Utc::now().timestamp() as u64;
         let file_name = format!("{}-{}", index, current_timestamp);
         let json = serde_json::to_string_pretty(&self)?;
-        fs::write(cache.join(&file_name), &json)?;
+        let path = cache.join(&file_name);
+        fs::write(&path, &json)?;
+        debug!(index, path:% = path.display(); "serie written to cache");
         return Ok(());
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Chapter {
     pub number: (u32, u32),
     pub name: String,
     pub providers: Vec<Provider>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Provider {
-    pub scan: String,
-    pub link: String,
-}
-
-impl Provider {
-    fn from_oneshot_provider_fragment(frag: ElementRef) -> Result<Self, SerieParseError> {
-        // Extract scan info.
-        let scan_selector = Selector::parse("span").expect("Selector is hardcoded.");
-        let Some(scan_node) = frag.select(&scan_selector).next() else {
-            return Err(SerieParseError::MissingScan);
-        };
-        let scan = match scan_node.text().next() {
-            Some(text) => text.trim().to_string(),
-            None => return Err(SerieParseError::MissingScan),
+impl Chapter {
+    pub fn new(number: (u32, u32), name: String, providers: Vec<Provider>) -> Chapter {
+        return Chapter {
+            number,
+            name,
+            providers,
         };
+    }
 
-        // Extract view url.
-        let a_selector = Selector::parse("a").expect("Selector is hardcoded.");
-        let Some(a_node) = frag.select(&a_selector).next() else {
-            return Err(SerieParseError::MissingScan);
-        };
-        let link = match a_node.attr("href") {
-            Some(text) => text.to_string(),
-            None => return Err(SerieParseError::MissingScan),
+    /// Urls of the pages, read from the reader of the first provider.
+    pub fn image_urls(&self, client: &Client) -> Result<Vec<String>, DownloadError> {
+        let Some(provider) = self.providers.first() else {
+            return Err(DownloadError::NoProvider);
         };
 
-        return Ok(Provider { scan, link });
+        let html = client.fetch(&provider.link)?;
+        debug!(url = provider.link.as_str(), bytes = html.len(); "reader page downloaded");
+        return client.site().parse_chapter(&html);
     }
 
-    fn from_serie_provider_fragment(frag: ElementRef) -> Result<Self, SerieParseError> {
-        let a_selector = Selector::parse("a").expect("Selector is hardcoded.");
+    /// Downloads every page into `folder`, named by their position.
+    pub fn download(&self, client: &Client, folder: &Path) -> Result<Vec<Page>, DownloadError> {
+        fs::create_dir_all(folder)?;
+        let mut pages: Vec<Page> = Vec::new();
+        for (i, url) in self.image_urls(client)?.iter().enumerate() {
+            pages.push(client.download_page(url, folder, &i.to_string())?);
+        }
+        return Ok(pages);
+    }
 
-        // Extract scan info.
-        let Some(scan_node) = frag.select(&a_selector).next() else {
-            return Err(SerieParseError::MissingScan);
-        };
-        let scan = match scan_node.text().next() {
-            Some(text) => text.trim().to_string(),
-            None => return Err(SerieParseError::MissingScan),
+    /// Async version of `Chapter::image_urls`.
+    #[cfg(feature = "async")]
+    pub async fn image_urls_async(
+        &self,
+        client: &AsyncClient,
+    ) -> Result<Vec<String>, DownloadError> {
+        let Some(provider) = self.providers.first() else {
+            return Err(DownloadError::NoProvider);
         };
 
-        // Extract view url.
-        let Some(link_node) = frag.select(&a_selector).last() else {
-            return Err(SerieParseError::MissingScan);
-        };
-        let link = match link_node.attr("href") {
-            Some(text) => text.to_string(),
-            None => return Err(SerieParseError::MissingScan),
-        };
+        let html = client.fetch(&provider.link).await?;
+        debug!(url = provider.link.as_str(), bytes = html.len(); "reader page downloaded");
+        return client.site().parse_chapter(&html);
+    }
 
-        return Ok(Provider { scan, link });
+    /// Async version of `Chapter::download`.
+    #[cfg(feature = "async")]
+    pub async fn download_async(
+        &self,
+        client: &AsyncClient,
+        folder: &Path,
+    ) -> Result<Vec<Page>, DownloadError> {
+        tokio::fs::create_dir_all(folder).await?;
+        let mut pages: Vec<Page> = Vec::new();
+        for (i, url) in self.image_urls_async(client).await?.iter().enumerate() {
+            pages.push(client.download_page(url, folder, &i.to_string()).await?);
+        }
+        return Ok(pages);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Provider {
+    pub scan: String,
+    pub link: String,
+}
+
+impl Provider {
+    pub fn new(scan: String, link: String) -> Provider {
+        return Provider { scan, link };
     }
 }
@@ -0,0 +1,196 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::fs;
+use std::io;
+use std::path::Path;
+use tmo_pdf_downloader::i18n::Msg;
+use tmo_pdf_downloader::models::serie::Serie;
+
+const DOWNLOADED_FILE: &str = "downloaded.json";
+
+/// Chapter picked in the interactive mode, with the provider to read it from.
+#[derive(Clone)]
+pub struct Pick {
+    pub chapter: usize,
+    pub provider: usize,
+    /// Image urls already read from the reader, fetched again when missing.
+    pub image_urls: Option<Vec<String>>,
+}
+
+struct Picker<'a> {
+    manga: &'a Serie,
+    // Scans each chapter was already written from.
+    downloaded: Vec<Vec<String>>,
+    selected: Vec<bool>,
+    providers: Vec<usize>,
+    state: ListState,
+}
+
+/// Shows the chapters of the serie in the terminal and returns the picked
+/// ones, nothing when the user quits. `cache_folder` is where the pages of
+/// each chapter are saved, it tells the scans each one was downloaded from.
+pub fn pick(manga: &Serie, cache_folder: &Path) -> io::Result<Vec<Pick>> {
+    let downloaded = manga
+        .chapters
+        .iter()
+        .map(|chapter| downloaded_scans(&cache_folder.join(&chapter.name)))
+        .collect();
+    let mut picker = Picker {
+        manga,
+        downloaded,
+        selected: vec![false; manga.chapters.len()],
+        providers: vec![0; manga.chapters.len()],
+        state: ListState::default().with_selected(Some(0)),
+    };
+
+    let mut terminal = ratatui::init();
+    let result = picker.run(&mut terminal);
+    ratatui::restore();
+    return result;
+}
+
+/// Scans the chapter cached in `folder` was written from, see `mark_downloaded`.
+pub fn downloaded_scans(folder: &Path) -> Vec<String> {
+    return fs::read_to_string(folder.join(DOWNLOADED_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+}
+
+/// Records that the chapter cached in `folder` was written from `scan`.
+/// Called once the output exists, so an interrupted download is not marked.
+pub fn mark_downloaded(folder: &Path, scan: &str) -> io::Result<()> {
+    let mut scans = downloaded_scans(folder);
+    if !scans.iter().any(|known| known == scan) {
+        scans.push(scan.to_string());
+    }
+    let json = serde_json::to_string(&scans)?;
+    return fs::write(folder.join(DOWNLOADED_FILE), json);
+}
+
+impl Picker<'_> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<Vec<Pick>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
+                KeyCode::Char(' ') => self.toggle(),
+                KeyCode::Char('a') => self.toggle_all(),
+                KeyCode::Left | KeyCode::Char('h') => self.cycle_provider(false),
+                KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => self.cycle_provider(true),
+                KeyCode::Enter => return Ok(self.picks()),
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(Vec::new()),
+                _ => {}
+            }
+        }
+    }
+
+    fn current(&self) -> Option<usize> {
+        return self
+            .state
+            .selected()
+            .map(|index| index.min(self.manga.chapters.len().saturating_sub(1)))
+            .filter(|index| *index < self.manga.chapters.len());
+    }
+
+    fn toggle(&mut self) {
+        if let Some(index) = self.current() {
+            self.selected[index] = !self.selected[index];
+        }
+    }
+
+    fn toggle_all(&mut self) {
+        let value = !self.selected.iter().all(|selected| *selected);
+        self.selected.fill(value);
+    }
+
+    fn cycle_provider(&mut self, forward: bool) {
+        let Some(index) = self.current() else {
+            return;
+        };
+        let count = self.manga.chapters[index].providers.len();
+        if count == 0 {
+            return;
+        }
+        self.providers[index] = if forward {
+            (self.providers[index] + 1) % count
+        } else {
+            (self.providers[index] + count - 1) % count
+        };
+    }
+
+    // The marked chapters, or the one under the cursor when none is marked.
+    fn picks(&self) -> Vec<Pick> {
+        let mut picks: Vec<Pick> = (0..self.manga.chapters.len())
+            .filter(|index| self.selected[*index])
+            .map(|index| Pick {
+                chapter: index,
+                provider: self.providers[index],
+                image_urls: None,
+            })
+            .collect();
+        if picks.is_empty()
+            && let Some(index) = self.current()
+        {
+            picks.push(Pick {
+                chapter: index,
+                provider: self.providers[index],
+                image_urls: None,
+            });
+        }
+        return picks;
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [list_area, help_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        let items: Vec<ListItem> = self
+            .manga
+            .chapters
+            .iter()
+            .enumerate()
+            .map(|(i, chapter)| {
+                let mark = if self.selected[i] { "[x]" } else { "[ ]" };
+                let scan = chapter
+                    .providers
+                    .get(self.providers[i])
+                    .map(|provider| provider.scan.as_str())
+                    .unwrap_or("-");
+                let status = if self.downloaded[i].iter().any(|known| known == scan) {
+                    Msg::Downloaded.to_string()
+                } else {
+                    String::new()
+                };
+                return ListItem::new(format!(
+                    "{} {}  {} ({}/{})  {}",
+                    mark,
+                    chapter.name,
+                    scan,
+                    self.providers[i] + 1,
+                    chapter.providers.len(),
+                    status
+                ));
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::bordered().title(self.manga.title.as_str()))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+        frame.render_widget(Paragraph::new(Msg::PickerHelp.to_string()), help_area);
+    }
+}
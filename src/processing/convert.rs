@@ -0,0 +1,152 @@
+use super::Page;
+use crate::errors::ProcessingError;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[non_exhaustive]
+pub enum ConvertFormat {
+    /// Lossy, uses the quality setting.
+    Jpeg,
+    /// Lossless.
+    Png,
+    /// Lossless, the encoder does not support lossy compression.
+    Webp,
+}
+
+impl ConvertFormat {
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            ConvertFormat::Jpeg => "jpg",
+            ConvertFormat::Png => "png",
+            ConvertFormat::Webp => "webp",
+        }
+    }
+
+    pub(crate) fn from_image_format(format: ImageFormat) -> Option<Self> {
+        match format {
+            ImageFormat::Jpeg => Some(ConvertFormat::Jpeg),
+            ImageFormat::Png => Some(ConvertFormat::Png),
+            ImageFormat::WebP => Some(ConvertFormat::Webp),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ConvertOptions {
+    /// Target format. `None` keeps the format of every source image.
+    pub format: Option<ConvertFormat>,
+    /// JPEG quality from 1 to 100.
+    pub quality: u8,
+    /// Images wider than this are downscaled keeping the aspect ratio.
+    pub max_width: Option<u32>,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        return ConvertOptions {
+            format: None,
+            quality: 85,
+            max_width: None,
+        };
+    }
+}
+
+/// Returns the file extension matching the real content of an image.
+pub fn image_extension(bytes: &[u8]) -> &'static str {
+    return match image::guess_format(bytes) {
+        Ok(format) => format.extensions_str().first().copied().unwrap_or("img"),
+        Err(_) => "img",
+    };
+}
+
+/// Re-encodes and downscales images following the given options.
+pub fn convert_images(
+    pages: &[Page],
+    folder: &Path,
+    options: &ConvertOptions,
+) -> Result<Vec<Page>, ProcessingError> {
+    let mut result: Vec<Page> = Vec::new();
+
+    for page in pages {
+        let reader = ImageReader::open(&page.path)?.with_guessed_format()?;
+        let format = options
+            .format
+            .or(reader.format().and_then(ConvertFormat::from_image_format))
+            .unwrap_or(ConvertFormat::Png);
+
+        let mut image = reader.decode()?;
+        let needs_resize = options.max_width.is_some_and(|max| image.width() > max);
+
+        if !needs_resize && options.format.is_none() {
+            result.push(page.clone());
+            continue;
+        }
+
+        if let Some(max_width) = options.max_width.filter(|_| needs_resize) {
+            let height = image.height() as f32 * max_width as f32 / image.width() as f32;
+            image = image.resize_exact(
+                max_width,
+                height.round().max(1.0) as u32,
+                FilterType::Lanczos3,
+            );
+        }
+
+        let stem = page
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("page");
+        let converted_path = folder.join(format!("{}-converted.{}", stem, format.extension()));
+        save_image(&image, &converted_path, format, options.quality)?;
+        result.push(page.with_path(converted_path));
+    }
+
+    return Ok(result);
+}
+
+pub fn save_image(
+    image: &DynamicImage,
+    path: &Path,
+    format: ConvertFormat,
+    quality: u8,
+) -> Result<(), ProcessingError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    encode_image(image, &mut writer, format, quality)?;
+    return Ok(());
+}
+
+/// Encodes the image into any writer, `quality` is only used by JPEG.
+pub fn encode_image<W: Write>(
+    image: &DynamicImage,
+    writer: W,
+    format: ConvertFormat,
+    quality: u8,
+) -> Result<(), ProcessingError> {
+    match format {
+        ConvertFormat::Jpeg => {
+            // JPEG has no alpha channel, grayscale images are kept with a single channel.
+            let encoder = JpegEncoder::new_with_quality(writer, quality.clamp(1, 100));
+            match image {
+                DynamicImage::ImageLuma8(_) => image.write_with_encoder(encoder)?,
+                _ => DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)?,
+            };
+        }
+        ConvertFormat::Png => image.write_with_encoder(PngEncoder::new(writer))?,
+        ConvertFormat::Webp => {
+            let encoder = WebPEncoder::new_lossless(writer);
+            DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(encoder)?;
+        }
+    };
+
+    return Ok(());
+}
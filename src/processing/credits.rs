@@ -0,0 +1,94 @@
+use super::{Page, load_image};
+use crate::errors::ProcessingError;
+use image::DynamicImage;
+use image::imageops::FilterType;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Hashes that match every scan.
+const GLOBAL_KEY: &str = "*";
+
+/// Maximum number of different bits between two hashes to consider them the same page.
+pub const MAX_DISTANCE: u32 = 6;
+
+/// Known credit page hashes, stored as a JSON object of scan name to hex hashes.
+#[derive(Debug, Clone)]
+pub struct CreditHashes {
+    hashes: HashMap<String, Vec<String>>,
+}
+
+impl CreditHashes {
+    /// Loads the hash list. A missing file is treated as an empty list.
+    pub fn from_file(path: &Path) -> Result<CreditHashes, ProcessingError> {
+        if !path.is_file() {
+            return Ok(CreditHashes {
+                hashes: HashMap::new(),
+            });
+        }
+
+        let contents = fs::read_to_string(path)?;
+        let hashes = serde_json::from_str(&contents)?;
+        return Ok(CreditHashes { hashes });
+    }
+
+    /// Returns the hashes registered for the scan plus the global ones.
+    pub fn for_scan(&self, scan: &str) -> Vec<u64> {
+        return [scan, GLOBAL_KEY]
+            .iter()
+            .filter_map(|key| self.hashes.get(*key))
+            .flatten()
+            .filter_map(|hex| u64::from_str_radix(hex.trim(), 16).ok())
+            .collect();
+    }
+}
+
+/// Difference hash: compares adjacent pixels of a 9x8 grayscale thumbnail.
+pub fn perceptual_hash(image: &DynamicImage) -> u64 {
+    let thumbnail = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash: u64 = 0;
+
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = thumbnail.get_pixel(x, y).0[0];
+            let right = thumbnail.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | (left > right) as u64;
+        }
+    }
+
+    return hash;
+}
+
+/// Returns the hex hash of every image, used to register new credit pages.
+pub fn hash_images(pages: &[Page]) -> Result<Vec<String>, ProcessingError> {
+    let mut result: Vec<String> = Vec::new();
+    for page in pages {
+        result.push(format!(
+            "{:016x}",
+            perceptual_hash(&load_image(&page.path)?)
+        ));
+    }
+    return Ok(result);
+}
+
+/// Removes the images whose hash is close to any of the known credit pages.
+pub fn drop_credit_pages(pages: &[Page], known: &[u64]) -> Result<Vec<Page>, ProcessingError> {
+    if known.is_empty() {
+        return Ok(pages.to_vec());
+    }
+
+    let mut result: Vec<Page> = Vec::new();
+
+    for page in pages {
+        let hash = perceptual_hash(&load_image(&page.path)?);
+        let is_credit = known
+            .iter()
+            .any(|credit| (credit ^ hash).count_ones() <= MAX_DISTANCE);
+
+        if !is_credit {
+            result.push(page.clone());
+        }
+    }
+
+    return Ok(result);
+}
@@ -0,0 +1,63 @@
+pub mod convert;
+pub mod credits;
+pub mod profile;
+pub mod split;
+pub mod spreads;
+pub mod stitch;
+pub mod trim;
+
+use crate::errors::ProcessingError;
+use image::{DynamicImage, ImageReader, Rgb, RgbImage};
+use std::path::{Path, PathBuf};
+
+/// Image file of a page and the url it was downloaded from.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Page {
+    pub path: PathBuf,
+    pub source: String,
+    /// The image could not be downloaded, the file is a blank page of the same size.
+    pub placeholder: bool,
+}
+
+impl Page {
+    pub fn new(path: PathBuf, source: String) -> Page {
+        return Page {
+            path,
+            source,
+            placeholder: false,
+        };
+    }
+
+    /// The same page stored in another file.
+    pub fn with_path(&self, path: PathBuf) -> Page {
+        return Page {
+            path,
+            source: self.source.clone(),
+            placeholder: self.placeholder,
+        };
+    }
+}
+
+/// Opens an image guessing its format from the content instead of the extension.
+pub fn load_image(path: &Path) -> Result<DynamicImage, ProcessingError> {
+    let image = ImageReader::open(path)?.with_guessed_format()?.decode()?;
+    return Ok(image);
+}
+
+/// Writes a blank light gray page to `folder` as `{name}.png`, standing for a
+/// page of `source` that could not be downloaded. The exports note it as missing.
+pub fn placeholder_page(
+    folder: &Path,
+    name: &str,
+    source: &str,
+    width: u32,
+    height: u32,
+) -> Result<Page, ProcessingError> {
+    let image = RgbImage::from_pixel(width.max(1), height.max(1), Rgb([224, 224, 224]));
+    let path = folder.join(format!("{}.png", name));
+    image.save(&path)?;
+    let mut page = Page::new(path, source.to_string());
+    page.placeholder = true;
+    return Ok(page);
+}
@@ -0,0 +1,128 @@
+use super::Page;
+use super::convert::{ConvertFormat, save_image};
+use crate::errors::ProcessingError;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, ImageReader};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[non_exhaustive]
+pub enum DeviceProfile {
+    /// Kindle Paperwhite, 1236x1648 grayscale.
+    Kindle,
+    /// Kobo Clara, 1072x1448 grayscale.
+    Kobo,
+    /// Color tablet, 1536x2048.
+    Tablet,
+}
+
+/// Container that works best on each device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreferredContainer {
+    Pdf,
+    Cbz,
+}
+
+#[non_exhaustive]
+pub struct ProfileSettings {
+    pub width: u32,
+    pub height: u32,
+    pub grayscale: bool,
+    /// Contrast adjustment, positive values increase it.
+    pub contrast: f32,
+    /// Values above 1 darken midtones, which e-ink screens render too light.
+    pub gamma: f32,
+    pub container: PreferredContainer,
+}
+
+impl DeviceProfile {
+    pub fn settings(&self) -> ProfileSettings {
+        match self {
+            DeviceProfile::Kindle => ProfileSettings {
+                width: 1236,
+                height: 1648,
+                grayscale: true,
+                contrast: 10.0,
+                gamma: 1.8,
+                container: PreferredContainer::Pdf,
+            },
+            DeviceProfile::Kobo => ProfileSettings {
+                width: 1072,
+                height: 1448,
+                grayscale: true,
+                contrast: 10.0,
+                gamma: 1.8,
+                container: PreferredContainer::Cbz,
+            },
+            DeviceProfile::Tablet => ProfileSettings {
+                width: 1536,
+                height: 2048,
+                grayscale: false,
+                contrast: 0.0,
+                gamma: 1.0,
+                container: PreferredContainer::Cbz,
+            },
+        }
+    }
+}
+
+/// Resizes the images to fit the device screen and applies its color adjustments.
+/// Images are saved as `format`, or in the format of each source image when `None`.
+pub fn apply_profile(
+    pages: &[Page],
+    folder: &Path,
+    settings: &ProfileSettings,
+    format: Option<ConvertFormat>,
+    quality: u8,
+) -> Result<Vec<Page>, ProcessingError> {
+    let mut result: Vec<Page> = Vec::new();
+
+    for page in pages {
+        let reader = ImageReader::open(&page.path)?.with_guessed_format()?;
+        let format = format
+            .or(reader.format().and_then(ConvertFormat::from_image_format))
+            .unwrap_or(ConvertFormat::Png);
+        let mut image = reader.decode()?;
+
+        if image.width() > settings.width || image.height() > settings.height {
+            image = image.resize(settings.width, settings.height, FilterType::Lanczos3);
+        }
+
+        if settings.grayscale {
+            let mut gray = image.to_luma8();
+            if settings.contrast != 0.0 {
+                gray = imageops::contrast(&gray, settings.contrast);
+            }
+            apply_gamma(&mut gray, settings.gamma);
+            image = DynamicImage::ImageLuma8(gray);
+        } else if settings.contrast != 0.0 {
+            image = image.adjust_contrast(settings.contrast);
+        }
+
+        let stem = page
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("page");
+        let profiled_path = folder.join(format!("{}-profile.{}", stem, format.extension()));
+        save_image(&image, &profiled_path, format, quality)?;
+        result.push(page.with_path(profiled_path));
+    }
+
+    return Ok(result);
+}
+
+fn apply_gamma(image: &mut GrayImage, gamma: f32) {
+    if gamma == 1.0 {
+        return;
+    }
+
+    let table: Vec<u8> = (0..=255u8)
+        .map(|v| (255.0 * (v as f32 / 255.0).powf(gamma)).round() as u8)
+        .collect();
+
+    for pixel in image.pixels_mut() {
+        pixel.0[0] = table[pixel.0[0] as usize];
+    }
+}
@@ -0,0 +1,101 @@
+use super::{Page, load_image};
+use crate::errors::ProcessingError;
+use image::{GenericImageView, GrayImage, ImageFormat};
+use std::path::Path;
+
+/// Maximum difference between the pixels of a row to consider it blank.
+const UNIFORM_TOLERANCE: u8 = 8;
+
+/// Slices images taller than `max_ratio` (height / width) into page-height chunks.
+/// The ratio must be finite and positive.
+/// Cuts are moved up to the closest blank band so panels are not split in half.
+pub fn split_tall_images(
+    pages: &[Page],
+    folder: &Path,
+    max_ratio: f32,
+) -> Result<Vec<Page>, ProcessingError> {
+    if !max_ratio.is_finite() || max_ratio <= 0.0 {
+        return Err(ProcessingError::InvalidRatio(max_ratio));
+    }
+
+    let mut result: Vec<Page> = Vec::new();
+
+    for page in pages {
+        // A missing page is noted once, it is not split.
+        if page.placeholder {
+            result.push(page.clone());
+            continue;
+        }
+        let image = load_image(&page.path)?;
+        let (width, height) = image.dimensions();
+        let page_height = (width as f32 * max_ratio).round().max(1.0) as u32;
+
+        if height <= page_height {
+            result.push(page.clone());
+            continue;
+        }
+
+        let stem = page
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("page");
+        let luma = image.to_luma8();
+        let mut top: u32 = 0;
+        let mut index: u32 = 0;
+
+        while top < height {
+            let bottom = if height - top <= page_height {
+                height
+            } else {
+                find_cut(&luma, top + page_height.div_ceil(2), top + page_height)
+            };
+
+            let chunk = image.crop_imm(0, top, width, bottom - top);
+            let chunk_path = folder.join(format!("{}-{}.png", stem, index));
+            chunk.save_with_format(&chunk_path, ImageFormat::Png)?;
+            result.push(page.with_path(chunk_path));
+
+            top = bottom;
+            index += 1;
+        }
+    }
+
+    return Ok(result);
+}
+
+/// Searches upwards from `max` for a uniform row, falling back to `max` if none is found before `min`.
+fn find_cut(luma: &GrayImage, min: u32, max: u32) -> u32 {
+    for y in (min..=max).rev() {
+        if is_uniform_row(luma, y) {
+            return y;
+        }
+    }
+    return max;
+}
+
+fn is_uniform_row(luma: &GrayImage, y: u32) -> bool {
+    let first = luma.get_pixel(0, y).0[0];
+    return (0..luma.width())
+        .all(|x| luma.get_pixel(x, y).0[0].abs_diff(first) <= UNIFORM_TOLERANCE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_ratios_that_are_not_positive() {
+        for ratio in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                split_tall_images(&[], Path::new("."), ratio),
+                Err(ProcessingError::InvalidRatio(_))
+            ));
+        }
+        assert!(
+            split_tall_images(&[], Path::new("."), 1.5)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
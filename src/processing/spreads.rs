@@ -0,0 +1,51 @@
+use super::{Page, load_image};
+use crate::errors::ProcessingError;
+use crate::models::serie::ReadingDirection;
+use image::{GenericImageView, ImageFormat};
+use std::path::Path;
+
+/// Splits landscape double-page spreads into two pages ordered by the reading direction.
+pub fn split_spreads(
+    pages: &[Page],
+    folder: &Path,
+    direction: ReadingDirection,
+) -> Result<Vec<Page>, ProcessingError> {
+    let mut result: Vec<Page> = Vec::new();
+
+    for page in pages {
+        // A missing page is noted once, it is not split.
+        if page.placeholder {
+            result.push(page.clone());
+            continue;
+        }
+        let image = load_image(&page.path)?;
+        let (width, height) = image.dimensions();
+
+        if width <= height {
+            result.push(page.clone());
+            continue;
+        }
+
+        let stem = page
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("page");
+        let half = width / 2;
+        let left = image.crop_imm(0, 0, half, height);
+        let right = image.crop_imm(half, 0, width - half, height);
+
+        let halves = match direction {
+            ReadingDirection::Rtl => [right, left],
+            ReadingDirection::Ltr => [left, right],
+        };
+
+        for (i, half_image) in halves.iter().enumerate() {
+            let half_path = folder.join(format!("{}-spread-{}.png", stem, i));
+            half_image.save_with_format(&half_path, ImageFormat::Png)?;
+            result.push(page.with_path(half_path));
+        }
+    }
+
+    return Ok(result);
+}
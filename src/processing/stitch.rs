@@ -0,0 +1,84 @@
+use super::{Page, load_image};
+use crate::errors::ProcessingError;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, ImageFormat, RgbaImage};
+use std::path::Path;
+
+/// Joins consecutive images vertically into strips of at most `max_height` pixels.
+/// Every image is scaled to the width of the first one of its strip, whose source is kept.
+/// Placeholders of missing pages end the strip and are kept on their own.
+pub fn stitch_images(
+    pages: &[Page],
+    folder: &Path,
+    max_height: u32,
+) -> Result<Vec<Page>, ProcessingError> {
+    let mut result: Vec<Page> = Vec::new();
+    let mut group: Vec<DynamicImage> = Vec::new();
+    let mut group_height: u32 = 0;
+    let mut group_page = pages.first();
+
+    for page in pages {
+        if page.placeholder {
+            if !group.is_empty() {
+                let strip_path = folder.join(format!("strip-{}.png", result.len()));
+                save_strip(&group, group_height, &strip_path)?;
+                result.extend(group_page.map(|first| first.with_path(strip_path)));
+                group.clear();
+                group_height = 0;
+            }
+            result.push(page.clone());
+            continue;
+        }
+        if group.is_empty() {
+            group_page = Some(page);
+        }
+        let mut image = load_image(&page.path)?;
+
+        // Height the image takes once scaled to the width of the current strip.
+        let scaled = group.first().map(|first| {
+            let height = image.height() as f32 * first.width() as f32 / image.width() as f32;
+            return (first.width(), height.round().max(1.0) as u32);
+        });
+
+        match scaled {
+            Some((_, height)) if group_height + height > max_height => {
+                // The image starts a new strip and keeps its own width.
+                let strip_path = folder.join(format!("strip-{}.png", result.len()));
+                save_strip(&group, group_height, &strip_path)?;
+                result.extend(group_page.map(|first| first.with_path(strip_path)));
+                group.clear();
+                group_height = 0;
+                group_page = Some(page);
+            }
+            Some((width, height)) if image.width() != width => {
+                image = image.resize_exact(width, height, FilterType::Lanczos3);
+            }
+            _ => {}
+        }
+
+        group_height += image.height();
+        group.push(image);
+    }
+
+    if !group.is_empty() {
+        let strip_path = folder.join(format!("strip-{}.png", result.len()));
+        save_strip(&group, group_height, &strip_path)?;
+        result.extend(group_page.map(|first| first.with_path(strip_path)));
+    }
+
+    return Ok(result);
+}
+
+fn save_strip(images: &[DynamicImage], height: u32, path: &Path) -> Result<(), ProcessingError> {
+    let width = images.iter().map(|i| i.width()).max().unwrap_or(1);
+    let mut strip = RgbaImage::new(width, height);
+    let mut top: i64 = 0;
+
+    for image in images {
+        imageops::replace(&mut strip, &image.to_rgba8(), 0, top);
+        top += image.dimensions().1 as i64;
+    }
+
+    strip.save_with_format(path, ImageFormat::Png)?;
+    return Ok(());
+}
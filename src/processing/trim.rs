@@ -0,0 +1,86 @@
+use super::{Page, load_image};
+use crate::errors::ProcessingError;
+use image::ImageFormat;
+use std::path::Path;
+
+/// Crops uniform borders, each side is compared with the color of its outer line.
+/// `tolerance` is the maximum luma difference accepted as border, and `max_fraction`
+/// caps how much of each side can be removed so artwork is never cut.
+pub fn trim_borders(
+    pages: &[Page],
+    folder: &Path,
+    tolerance: u8,
+    max_fraction: f32,
+) -> Result<Vec<Page>, ProcessingError> {
+    let mut result: Vec<Page> = Vec::new();
+
+    for page in pages {
+        let image = load_image(&page.path)?;
+        let luma = image.to_luma8();
+        let (width, height) = luma.dimensions();
+        let max_x = (width as f32 * max_fraction) as u32;
+        let max_y = (height as f32 * max_fraction) as u32;
+
+        // Each side has its own color, the median shade of its outer line.
+        let top_color = median((0..width).map(|x| luma.get_pixel(x, 0).0[0]));
+        let bottom_color = median((0..width).map(|x| luma.get_pixel(x, height - 1).0[0]));
+        let left_color = median((0..height).map(|y| luma.get_pixel(0, y).0[0]));
+        let right_color = median((0..height).map(|y| luma.get_pixel(width - 1, y).0[0]));
+        let is_border =
+            |x: u32, y: u32, color: u8| luma.get_pixel(x, y).0[0].abs_diff(color) <= tolerance;
+
+        // Rows are checked between the side borders and columns between the top
+        // and bottom ones, so borders of different colors do not block each other.
+        let (mut top, mut bottom, mut left, mut right) = (0, 0, 0, 0);
+        for _ in 0..4 {
+            if top + bottom >= height || left + right >= width {
+                break;
+            }
+            let (xs, ys) = (left..width - right, top..height - bottom);
+            let row_is_border = |y: u32, color: u8| xs.clone().all(|x| is_border(x, y, color));
+            let column_is_border = |x: u32, color: u8| ys.clone().all(|y| is_border(x, y, color));
+
+            let sides = (
+                count_while(0..max_y, |y| row_is_border(y, top_color)),
+                count_while((0..height).rev().take(max_y as usize), |y| {
+                    row_is_border(y, bottom_color)
+                }),
+                count_while(0..max_x, |x| column_is_border(x, left_color)),
+                count_while((0..width).rev().take(max_x as usize), |x| {
+                    column_is_border(x, right_color)
+                }),
+            );
+            if sides == (top, bottom, left, right) {
+                break;
+            }
+            (top, bottom, left, right) = sides;
+        }
+
+        if top + bottom + left + right == 0 || top + bottom >= height || left + right >= width {
+            result.push(page.clone());
+            continue;
+        }
+
+        let trimmed = image.crop_imm(left, top, width - left - right, height - top - bottom);
+        let stem = page
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("page");
+        let trimmed_path = folder.join(format!("{}-trimmed.png", stem));
+        trimmed.save_with_format(&trimmed_path, ImageFormat::Png)?;
+        result.push(page.with_path(trimmed_path));
+    }
+
+    return Ok(result);
+}
+
+fn count_while(range: impl Iterator<Item = u32>, predicate: impl Fn(u32) -> bool) -> u32 {
+    return range.take_while(|&value| predicate(value)).count() as u32;
+}
+
+fn median(values: impl Iterator<Item = u8>) -> u8 {
+    let mut values: Vec<u8> = values.collect();
+    values.sort_unstable();
+    return values.get(values.len() / 2).copied().unwrap_or(0);
+}
@@ -0,0 +1,223 @@
+use indicatif::{HumanBytes, HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+use tmo_pdf_downloader::export::chapter::{ExportEvent, ExportStep};
+use tmo_pdf_downloader::i18n::Msg;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    /// Animated bars, only when stderr is a terminal.
+    Bars,
+    /// One line per update, for logs and pipes.
+    Lines,
+    Quiet,
+}
+
+/// Reports the progress of the long running steps.
+pub struct Progress {
+    mode: Mode,
+    multi: MultiProgress,
+}
+
+impl Progress {
+    pub fn new(quiet: bool) -> Progress {
+        let mode = if quiet {
+            Mode::Quiet
+        } else if io::stderr().is_terminal() {
+            Mode::Bars
+        } else {
+            Mode::Lines
+        };
+
+        return Progress {
+            mode,
+            multi: MultiProgress::new(),
+        };
+    }
+
+    /// Bars that have to be hidden while printing other lines to stderr.
+    pub fn bars(&self) -> Option<MultiProgress> {
+        return match self.mode {
+            Mode::Bars => Some(self.multi.clone()),
+            _ => None,
+        };
+    }
+
+    /// Task with a known number of items, like chapters or pages.
+    pub fn bar(&self, label: &str, total: u64) -> Task {
+        let style =
+            ProgressStyle::with_template("{prefix:>12} [{bar:30}] {pos}/{len} {msg} ETA {eta}")
+                .expect("Template is hardcoded.")
+                .progress_chars("=> ");
+
+        return self.task(ProgressBar::new(total).with_style(style), label, total);
+    }
+
+    /// Task without a known length, like a processing step.
+    pub fn spinner(&self, label: &str) -> Task {
+        let style = ProgressStyle::with_template("{prefix:>12} {spinner} {msg} {elapsed}")
+            .expect("Template is hardcoded.");
+
+        let task = self.task(ProgressBar::new_spinner().with_style(style), label, 0);
+        if let Some(bar) = &task.bar {
+            bar.enable_steady_tick(Duration::from_millis(120));
+        }
+        return task;
+    }
+
+    fn task(&self, bar: ProgressBar, label: &str, total: u64) -> Task {
+        let bar = match self.mode {
+            Mode::Bars => Some(self.multi.add(bar.with_prefix(label.to_string()))),
+            _ => None,
+        };
+
+        return Task {
+            mode: self.mode,
+            bar,
+            label: label.to_string(),
+            total,
+            position: 0,
+            bytes: 0,
+            started: Instant::now(),
+        };
+    }
+}
+
+/// A single step tracked by `Progress`.
+pub struct Task {
+    mode: Mode,
+    bar: Option<ProgressBar>,
+    label: String,
+    total: u64,
+    position: u64,
+    bytes: u64,
+    started: Instant,
+}
+
+impl Task {
+    /// Marks one item as done, `bytes` is used to show the download speed.
+    pub fn inc(&mut self, bytes: u64) {
+        self.position += 1;
+        self.bytes += bytes;
+
+        let elapsed = self.started.elapsed().as_secs_f64().max(0.001);
+        let speed = if self.bytes > 0 {
+            format!("{}/s", HumanBytes((self.bytes as f64 / elapsed) as u64))
+        } else {
+            String::new()
+        };
+
+        match self.mode {
+            Mode::Bars => {
+                if let Some(bar) = &self.bar {
+                    bar.set_message(speed);
+                    bar.inc(1);
+                }
+            }
+            Mode::Lines => {
+                let remaining = self.total.saturating_sub(self.position);
+                let eta =
+                    Duration::from_secs_f64(elapsed / self.position as f64 * remaining as f64);
+                eprintln!(
+                    "{}: {}/{} {} ETA {}",
+                    self.label,
+                    self.position,
+                    self.total,
+                    speed,
+                    HumanDuration(eta)
+                );
+            }
+            Mode::Quiet => {}
+        }
+    }
+
+    /// Ends the task leaving a short summary.
+    pub fn finish(self, summary: &str) {
+        match self.mode {
+            Mode::Bars => {
+                if let Some(bar) = &self.bar {
+                    bar.finish_with_message(summary.to_string());
+                }
+            }
+            Mode::Lines => eprintln!(
+                "{}: {} ({})",
+                self.label,
+                summary,
+                HumanDuration(self.started.elapsed())
+            ),
+            Mode::Quiet => {}
+        }
+    }
+}
+
+/// Shows the steps of a chapter export, one task at a time.
+pub struct ExportTasks<'a> {
+    progress: &'a Progress,
+    task: Option<Task>,
+    // Pages the current step started with.
+    pages: usize,
+}
+
+impl ExportTasks<'_> {
+    pub fn new(progress: &Progress) -> ExportTasks<'_> {
+        return ExportTasks {
+            progress,
+            task: None,
+            pages: 0,
+        };
+    }
+
+    pub fn report(&mut self, event: ExportEvent) {
+        match event {
+            ExportEvent::Started(step, pages) => {
+                self.pages = pages;
+                let label = step_label(step).to_string();
+                self.task = Some(match step {
+                    ExportStep::Download => self.progress.bar(&label, pages as u64),
+                    _ => self.progress.spinner(&label),
+                });
+            }
+            ExportEvent::PageSaved(bytes) => {
+                if let Some(task) = &mut self.task {
+                    task.inc(bytes);
+                }
+            }
+            ExportEvent::Finished(step, pages) => {
+                let summary = match step {
+                    ExportStep::Download => Msg::PagesSaved,
+                    ExportStep::Credits => Msg::CreditsDropped(self.pages.saturating_sub(pages)),
+                    ExportStep::Trim => Msg::BordersTrimmed,
+                    ExportStep::Spreads => Msg::SpreadsSplit(pages),
+                    ExportStep::Split => Msg::TallSplit(pages),
+                    ExportStep::Stitch => Msg::Stitched(pages),
+                    ExportStep::Convert => Msg::Converted,
+                    ExportStep::Profile => Msg::ProfileApplied,
+                    ExportStep::Output => Msg::PagesWritten(pages),
+                };
+                if let Some(task) = self.task.take() {
+                    task.finish(&summary.to_string());
+                }
+            }
+            ExportEvent::Hashes(hashes) => {
+                for (i, hash) in hashes.iter().enumerate() {
+                    println!("{}: {}", Msg::PageHash(i), hash);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn step_label(step: ExportStep) -> Msg {
+    return match step {
+        ExportStep::Download => Msg::PagesStep,
+        ExportStep::Credits => Msg::CreditsStep,
+        ExportStep::Trim => Msg::TrimStep,
+        ExportStep::Spreads => Msg::SpreadsStep,
+        ExportStep::Split => Msg::SplitStep,
+        ExportStep::Stitch => Msg::StitchStep,
+        ExportStep::Convert => Msg::ConvertStep,
+        ExportStep::Profile => Msg::ProfileStep,
+        ExportStep::Output => Msg::OutputStep,
+    };
+}
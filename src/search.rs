@@ -0,0 +1,93 @@
+use crate::app::{App, RunError, Target};
+use log::{info, warn};
+use std::io::{self, BufRead, IsTerminal, Write};
+use tmo_pdf_downloader::i18n::Msg;
+use tmo_pdf_downloader::models::search::SearchResult;
+use tmo_pdf_downloader::sites::SiteUrl;
+
+/// Searches every site and lets the user pick a result when stdin is a terminal.
+pub fn run(app: &App, query: &str) -> Result<(), RunError> {
+    let task = app.progress.spinner(&Msg::SearchStep.to_string());
+    let mut results: Vec<SearchResult> = Vec::new();
+    for site in app.sites.iter() {
+        match app.client_for(site.clone()).search(query) {
+            Ok(found) => results.extend(found),
+            Err(error) => warn!(site = site.name(), error:%; "search failed"),
+        }
+    }
+    task.finish(&Msg::ResultsFound(results.len()).to_string());
+
+    if results.is_empty() {
+        println!("{}", Msg::NoResults);
+        return Ok(());
+    }
+
+    for (i, result) in results.iter().enumerate() {
+        let score = match result.score {
+            Some(score) => format!("{:.2}", score),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:>3}. {} [{}] #{} {} {}",
+            i + 1,
+            result.title,
+            result.kind,
+            result.index,
+            score,
+            result.url
+        );
+    }
+
+    // Only the list is printed when the output is piped.
+    if !io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    let Some(result) = prompt(&Msg::PickResult)
+        .and_then(|answer| answer.parse::<usize>().ok())
+        .and_then(|number| results.get(number.checked_sub(1)?))
+    else {
+        return Ok(());
+    };
+
+    let url_info = match app.sites.parse_url(&result.url) {
+        Ok((_, SiteUrl::Serie(url_info))) => url_info,
+        _ => {
+            warn!(url = result.url.as_str(); "the result is not a serie url");
+            return Ok(());
+        }
+    };
+    info!(index = result.index, title = result.title.as_str(); "result picked");
+
+    match prompt(&Msg::PickAction).as_deref() {
+        Some("l") => {
+            let manga = app.load_serie(url_info.index, Some(url_info))?;
+            println!("{} - {}", manga.title, Msg::ChapterList);
+            for (i, chapter) in manga.chapters.iter().enumerate() {
+                let scans: Vec<&str> = chapter
+                    .providers
+                    .iter()
+                    .map(|provider| provider.scan.as_str())
+                    .collect();
+                println!("{:>4}. {} ({})", i, chapter.name, scans.join(", "));
+            }
+        }
+        Some("d") => app.download(Target::Latest(url_info))?,
+        _ => (),
+    }
+    return Ok(());
+}
+
+// Prints the question and reads one trimmed, lowercased line.
+fn prompt(msg: &Msg) -> Option<String> {
+    print!("{}", msg);
+    io::stdout().flush().ok()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).ok()?;
+    let answer = answer.trim().to_lowercase();
+    if answer.is_empty() {
+        return None;
+    }
+    return Some(answer);
+}
@@ -0,0 +1,165 @@
+pub mod tmo;
+
+use crate::errors::{DownloadError, SerieParseError, UrlError};
+use crate::models::search::SearchResult;
+use crate::models::serie::{Serie, SerieUrlInfo};
+use scraper::{Html, Selector};
+use std::fmt::Debug;
+use std::sync::Arc;
+use tmo::Tmo;
+use url::Url;
+
+/// Everything that is specific to a site: which urls it owns and how its pages are read.
+pub trait SiteAdapter: Debug + Send + Sync {
+    /// Short unique name, stored in the cache to find the adapter again.
+    fn name(&self) -> &'static str;
+
+    /// Whether the url belongs to the site.
+    fn recognizes(&self, url: &Url) -> bool;
+
+    /// Extracts the info of a serie url.
+    fn parse_url(&self, url: &Url) -> Result<SerieUrlInfo, UrlError>;
+
+    /// Urls that lead to the page of a serie knowing only its id,
+    /// the site is expected to redirect or link to the canonical one.
+    fn id_urls(&self, _index: u32) -> Vec<String> {
+        return Vec::new();
+    }
+
+    /// Url the page declares as its canonical one, from `link[rel=canonical]` or `og:url`.
+    fn canonical_url(&self, html: &str) -> Option<String> {
+        let document = Html::parse_document(html);
+        let selector = Selector::parse("link[rel='canonical'], meta[property='og:url']")
+            .expect("Selector is hardcoded.");
+        return document
+            .select(&selector)
+            .find_map(|node| node.attr("href").or(node.attr("content")))
+            .map(|url| url.to_string());
+    }
+
+    /// Canonical form of the url when it points to the reader of a chapter.
+    fn reader_url(&self, _url: &Url) -> Option<Url> {
+        return None;
+    }
+
+    /// Extracts what the reader page at `url` tells about the chapter and its serie.
+    fn parse_reader(&self, _url: &Url, _html: &str) -> ReaderInfo {
+        return ReaderInfo::default();
+    }
+
+    /// Whether two reader urls show the same upload of a chapter.
+    fn same_reader(&self, a: &Url, b: &Url) -> bool {
+        return a == b;
+    }
+
+    /// Extracts the title, cover and chapters from the page of a serie.
+    fn parse_serie(&self, url_info: SerieUrlInfo, html: &str) -> Result<Serie, SerieParseError>;
+
+    /// Extracts the image urls from the reader page of a chapter.
+    fn parse_chapter(&self, html: &str) -> Result<Vec<String>, DownloadError>;
+
+    /// Url of the search page for a query, if the site has one.
+    fn search_url(&self, _query: &str) -> Option<String> {
+        return None;
+    }
+
+    /// Extracts the results of a search page.
+    fn parse_search(&self, _html: &str) -> Vec<SearchResult> {
+        return Vec::new();
+    }
+
+    /// Headers sent when downloading an image as name and value, some servers check the referer.
+    fn image_headers(&self, _url: &str) -> Vec<(String, String)> {
+        return Vec::new();
+    }
+}
+
+/// A url given by the user, of a serie or of a single chapter.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum SiteUrl {
+    Serie(SerieUrlInfo),
+    Reader(String),
+}
+
+/// Chapter details found in a reader page, every field is optional.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ReaderInfo {
+    /// Url of the serie the chapter belongs to.
+    pub serie_url: Option<String>,
+    pub chapter_name: Option<String>,
+    pub chapter_number: Option<(u32, u32)>,
+    /// Name of the scan that uploaded the chapter.
+    pub scan: Option<String>,
+}
+
+/// Known sites, the first one recognizing an url handles it.
+#[derive(Debug, Clone)]
+pub struct SiteRegistry {
+    sites: Vec<Arc<dyn SiteAdapter>>,
+}
+
+impl Default for SiteRegistry {
+    fn default() -> Self {
+        let mut registry = SiteRegistry::empty();
+        registry.register(Arc::new(Tmo::default()));
+        return registry;
+    }
+}
+
+impl SiteRegistry {
+    pub fn empty() -> SiteRegistry {
+        return SiteRegistry { sites: Vec::new() };
+    }
+
+    /// Adds a site, it takes precedence over the ones already registered.
+    pub fn register(&mut self, site: Arc<dyn SiteAdapter>) {
+        self.sites.insert(0, site);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn SiteAdapter>> {
+        return self.sites.iter();
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<Arc<dyn SiteAdapter>> {
+        return self.sites.iter().find(|site| site.name() == name).cloned();
+    }
+
+    pub fn find(&self, url: &Url) -> Option<Arc<dyn SiteAdapter>> {
+        return self.sites.iter().find(|site| site.recognizes(url)).cloned();
+    }
+
+    /// Finds the site of a serie or reader url and extracts its info.
+    pub fn parse_url(&self, s: &str) -> Result<(Arc<dyn SiteAdapter>, SiteUrl), UrlError> {
+        let url = Url::parse(s).map_err(|_| UrlError::Invalid)?;
+        let Some(site) = self.find(&url) else {
+            return Err(UrlError::UnknownHost);
+        };
+        if let Some(reader) = site.reader_url(&url) {
+            return Ok((site, SiteUrl::Reader(reader.to_string())));
+        }
+        let url_info = site.parse_url(&url)?;
+        return Ok((site, SiteUrl::Serie(url_info)));
+    }
+}
+
+/// Finds the serie with the given id in a page reached from `SiteAdapter::id_urls`,
+/// looking at the canonical url of the page and at the url it was redirected to.
+pub(crate) fn match_id(
+    site: &dyn SiteAdapter,
+    index: u32,
+    final_url: &Url,
+    html: &str,
+) -> Option<SerieUrlInfo> {
+    let canonical = site
+        .canonical_url(html)
+        .and_then(|url| Url::parse(&url).ok());
+
+    // The canonical url is preferred, some sites answer to any type or slug.
+    return [canonical, Some(final_url.clone())]
+        .into_iter()
+        .flatten()
+        .filter_map(|url| site.parse_url(&url).ok())
+        .find(|url_info| url_info.index == index);
+}
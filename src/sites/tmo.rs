@@ -0,0 +1,697 @@
+use super::{ReaderInfo, SiteAdapter};
+use crate::errors::{DownloadError, SerieParseError, UrlError};
+use crate::models::search::SearchResult;
+use crate::models::serie::{Chapter, Provider, Serie, SerieUrlInfo};
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use url::Url;
+
+/// Domain the site currently lives on.
+pub const BASE_DOMAIN: &str = "zonatmo.com";
+
+/// Older and alternative domains of the site, they share the same url layout.
+pub const MIRROR_DOMAINS: &[&str] = &["lectortmo.com", "visortmo.com"];
+
+/// Types of the library, in the order they are tried when resolving an id.
+const LIBRARY_KINDS: &[&str] = &[
+    "manga",
+    "manhwa",
+    "manhua",
+    "one_shot",
+    "doujinshi",
+    "novel",
+    "oel",
+];
+
+/// TuMangaOnline, at zonatmo.com and its mirrors.
+#[derive(Debug, Clone)]
+pub struct Tmo {
+    base_domain: String,
+    mirrors: Vec<String>,
+}
+
+impl Default for Tmo {
+    fn default() -> Self {
+        return Tmo {
+            base_domain: BASE_DOMAIN.to_string(),
+            mirrors: MIRROR_DOMAINS.iter().map(|d| d.to_string()).collect(),
+        };
+    }
+}
+
+impl Tmo {
+    /// Recognizes urls from another domain of the site.
+    pub fn with_mirror(mut self, domain: &str) -> Tmo {
+        self.mirrors.push(normalize_domain(domain));
+        return self;
+    }
+
+    /// Moves the site to another domain, urls are rewritten to it.
+    /// The previous domain is still recognized as a mirror.
+    pub fn with_base_domain(mut self, domain: &str) -> Tmo {
+        let previous = std::mem::replace(&mut self.base_domain, normalize_domain(domain));
+        self.mirrors.push(previous);
+        return self;
+    }
+
+    pub fn base_domain(&self) -> &str {
+        return &self.base_domain;
+    }
+
+    // The same url on the base domain over https, without port or fragment.
+    fn canonical(&self, url: &Url) -> Option<Url> {
+        let mut url = url.clone();
+        url.set_scheme("https").ok()?;
+        url.set_port(None).ok()?;
+        url.set_host(Some(&self.base_domain)).ok()?;
+        url.set_fragment(None);
+        return Some(url);
+    }
+}
+
+// Hosts are compared without the www. prefix and in lowercase.
+fn normalize_domain(domain: &str) -> String {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    return match domain.strip_prefix("www.") {
+        Some(rest) => rest.to_string(),
+        None => domain,
+    };
+}
+
+impl SiteAdapter for Tmo {
+    fn name(&self) -> &'static str {
+        return "tmo";
+    }
+
+    fn recognizes(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = normalize_domain(host);
+        return host == self.base_domain || self.mirrors.contains(&host);
+    }
+
+    /// Extracts the info of a serie url like `https://zonatmo.com/library/manga/12345/name`.
+    /// Urls from mirrors are rewritten to the base domain.
+    fn parse_url(&self, url: &Url) -> Result<SerieUrlInfo, UrlError> {
+        // Validate the URL host
+        if !self.recognizes(url) {
+            return Err(UrlError::UnknownHost);
+        };
+
+        let mut url = self.canonical(url).ok_or(UrlError::UnknownHost)?;
+        url.set_query(None);
+
+        // Get the path segments
+        let segments: Vec<&str> = match url.path_segments() {
+            Some(s) => s.collect(),
+            None => return Err(UrlError::MissingPath),
+        };
+
+        // Validate the path structure and extract the ID
+        if segments.len() < 4 || segments[0] != "library" {
+            return Err(UrlError::InvalidFormat);
+        };
+
+        let index = segments[2]
+            .parse::<u32>()
+            .map_err(|_| UrlError::InvalidId)?;
+
+        return Ok(SerieUrlInfo {
+            url: url.to_string(),
+            index,
+            slug: segments[3].to_string(),
+            is_oneshot: segments[1] == "one_shot",
+            kind: segments[1].to_string(),
+            site: self.name().to_string(),
+        });
+    }
+
+    /// The library redirects to the right type and slug when they do not match the id.
+    fn id_urls(&self, index: u32) -> Vec<String> {
+        return LIBRARY_KINDS
+            .iter()
+            .map(|kind| format!("https://{}/library/{}/{}/-", self.base_domain, kind, index))
+            .collect();
+    }
+
+    /// Recognizes `/view_uploads/{id}` and `/viewer/{hash}/{paginated|cascade}` urls.
+    fn reader_url(&self, url: &Url) -> Option<Url> {
+        if !self.recognizes(url) {
+            return None;
+        }
+
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+        let is_reader = matches!(
+            segments.as_slice(),
+            ["view_uploads", _] | ["viewer", _, "paginated" | "cascade", ..]
+        );
+        if !is_reader {
+            return None;
+        }
+
+        return self.canonical(url);
+    }
+
+    /// The reader links back to the serie and to the group of the scan,
+    /// and shows the chapter number in a heading.
+    fn parse_reader(&self, url: &Url, html: &str) -> ReaderInfo {
+        let document = Html::parse_document(html);
+
+        let link_selector =
+            Selector::parse("a[href*='/library/']").expect("Selector is hardcoded.");
+        let serie_url = document
+            .select(&link_selector)
+            .filter_map(|node| node.attr("href"))
+            .filter_map(|href| url.join(href).ok())
+            .find_map(|url| self.parse_url(&url).ok())
+            .map(|url_info| url_info.url);
+
+        let scan_selector = Selector::parse("a[href*='/groups/']").expect("Selector is hardcoded.");
+        let scan = document
+            .select(&scan_selector)
+            .map(|node| node.text().collect::<String>().trim().to_string())
+            .find(|name| !name.is_empty());
+
+        let heading_selector = Selector::parse("h1, h2, h3, h4").expect("Selector is hardcoded.");
+        let regex = Regex::new(r"Capítulo\s+(\d+)\.(\d+)").expect("Regex is hardcoded.");
+        let chapter_name = document
+            .select(&heading_selector)
+            .map(|node| node.text().collect::<String>().trim().to_string())
+            .find(|text| regex.is_match(text));
+        let chapter_number = chapter_name
+            .as_ref()
+            .and_then(|name| regex.captures(name))
+            .and_then(|numbers| Some((numbers[1].parse().ok()?, numbers[2].parse().ok()?)));
+
+        return ReaderInfo {
+            serie_url,
+            chapter_name,
+            chapter_number,
+            scan,
+        };
+    }
+
+    /// `view_uploads` links redirect to `viewer/{hash}/paginated` and the
+    /// reader can also be shown as `cascade`, the hash tells the upload.
+    fn same_reader(&self, a: &Url, b: &Url) -> bool {
+        let upload = |url: &Url| {
+            let url = self.canonical(url)?;
+            let mut segments = url.path_segments()?;
+            if segments.next()? != "viewer" {
+                return None;
+            }
+            return Some(segments.next()?.to_string());
+        };
+        return match (upload(a), upload(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.canonical(a) == self.canonical(b),
+        };
+    }
+
+    /// Extracts info from the url and body of a manga html page.
+    fn parse_serie(&self, url_info: SerieUrlInfo, html: &str) -> Result<Serie, SerieParseError> {
+        let document = Html::parse_document(html);
+
+        // Extracts title from the body.
+        let title_selector = Selector::parse("h1.element-title").expect("Selector is hardcoded.");
+        let Some(title_node) = document.select(&title_selector).next() else {
+            return Err(SerieParseError::MissingTitle);
+        };
+        let title = match title_node.text().next() {
+            Some(text) => text.trim().to_string(),
+            None => return Err(SerieParseError::MissingTitle),
+        };
+
+        // Extracts cover url from the body, it is optional.
+        let cover_selector = Selector::parse("img.book-thumbnail").expect("Selector is hardcoded.");
+        let cover_url = document
+            .select(&cover_selector)
+            .next()
+            .and_then(|node| node.attr("src"))
+            .map(|src| src.to_string());
+
+        // If the serie is a one_shot we must extract providers in a different way.
+        if url_info.is_oneshot {
+            let providers_selector =
+                Selector::parse("li.list-group-item").expect("Selector is hardcoded.");
+            let provider_nodes = document.select(&providers_selector);
+
+            let providers: Vec<Provider> = provider_nodes
+                .filter_map(|j| oneshot_provider(j).ok())
+                .collect();
+
+            let chapters = vec![Chapter {
+                number: (0, 0),
+                name: title.clone(),
+                providers,
+            }];
+
+            return Ok(Serie {
+                url_info,
+                title,
+                cover_url,
+                chapters,
+            });
+        }
+
+        // Extracts chapters from the body.
+        let chapters_selector =
+            Selector::parse("div#chapters li.upload-link").expect("Selector is hardcoded.");
+        let chapters_nodes = document.select(&chapters_selector);
+        let mut chapters: Vec<Chapter> = Vec::new();
+        let regex = Regex::new(r"Capítulo\s+(\d+)\.(\d+)").expect("Regex is hardcoded.");
+
+        for chapter_node in chapters_nodes.rev() {
+            // Extracts chapter's title.
+            let name_selector = Selector::parse("h4 a").expect("Selector is hardcoded.");
+            let Some(name_node) = chapter_node.select(&name_selector).next() else {
+                return Err(SerieParseError::MissingTitle);
+            };
+            let name = match name_node.text().next() {
+                Some(text) => text.trim().to_string(),
+                None => return Err(SerieParseError::MissingTitle),
+            };
+
+            // Parses title to extract number
+            let Some(numbers) = regex.captures(&name) else {
+                return Err(SerieParseError::MissingTitle);
+            };
+            let Ok(complete_part) = numbers
+                .get(1)
+                .expect("It selects the first capture group.")
+                .as_str()
+                .parse::<u32>()
+            else {
+                return Err(SerieParseError::MissingTitle);
+            };
+            let Ok(decimal_part) = numbers
+                .get(2)
+                .expect("It selects the second capture group.")
+                .as_str()
+                .parse::<u32>()
+            else {
+                return Err(SerieParseError::MissingTitle);
+            };
+
+            let number = (complete_part, decimal_part);
+
+            // Extracts providers of the chapter.
+            let providers_selector =
+                Selector::parse("li.list-group-item").expect("Selector is hardcoded.");
+            let providers_nodes = chapter_node.select(&providers_selector);
+
+            let providers: Vec<Provider> = providers_nodes
+                .filter_map(|j| serie_provider(j).ok())
+                .collect();
+
+            chapters.push(Chapter {
+                number,
+                name,
+                providers,
+            });
+        }
+
+        return Ok(Serie {
+            url_info,
+            title,
+            cover_url,
+            chapters,
+        });
+    }
+
+    fn search_url(&self, query: &str) -> Option<String> {
+        let base = format!("https://{}/library", self.base_domain);
+        let url = Url::parse_with_params(&base, &[("_pg", "1"), ("title", query)]).ok()?;
+        return Some(url.to_string());
+    }
+
+    /// Every result is a `div.element` with a link to the serie, its title, type and score.
+    fn parse_search(&self, html: &str) -> Vec<SearchResult> {
+        let document = Html::parse_document(html);
+        let element_selector = Selector::parse("div.element").expect("Selector is hardcoded.");
+        let link_selector = Selector::parse("a").expect("Selector is hardcoded.");
+        let title_selector = Selector::parse("h4.text-truncate").expect("Selector is hardcoded.");
+        let kind_selector = Selector::parse("span.book-type").expect("Selector is hardcoded.");
+        let score_selector = Selector::parse("span.score span").expect("Selector is hardcoded.");
+
+        let Ok(base) = Url::parse(&format!("https://{}/", self.base_domain)) else {
+            return Vec::new();
+        };
+
+        let mut results: Vec<SearchResult> = Vec::new();
+        for element in document.select(&element_selector) {
+            // Extracts id and url from the link, results without them are skipped.
+            // Links may be relative to the site.
+            let Some(url_info) = element
+                .select(&link_selector)
+                .filter_map(|node| node.attr("href"))
+                .filter_map(|href| base.join(href.trim()).ok())
+                .find_map(|url| self.parse_url(&url).ok())
+            else {
+                continue;
+            };
+
+            let title = match element.select(&title_selector).next() {
+                Some(node) => match node.attr("title") {
+                    Some(title) => title.trim().to_string(),
+                    None => node.text().collect::<String>().trim().to_string(),
+                },
+                None => url_info.slug.clone(),
+            };
+
+            let kind = match element.select(&kind_selector).next() {
+                Some(node) => node.text().collect::<String>().trim().to_lowercase(),
+                None => url_info.kind.clone(),
+            };
+
+            let score = element
+                .select(&score_selector)
+                .next()
+                .and_then(|node| node.text().collect::<String>().trim().parse::<f32>().ok());
+
+            results.push(SearchResult {
+                title,
+                kind,
+                index: url_info.index,
+                url: url_info.url,
+                score,
+            });
+        }
+        return results;
+    }
+
+    // Extracts the image urls from a paginated or cascade reader page.
+    fn parse_chapter(&self, html: &str) -> Result<Vec<String>, DownloadError> {
+        let result = if html.contains("var dirPath = '") {
+            //Implement more secure detection
+            debug!(mode = "paginated"; "reader detected");
+            paginated(html)
+        } else {
+            debug!(mode = "cascade"; "reader detected");
+            cascade(html)
+        };
+
+        let urls = result?;
+        debug!(pages = urls.len(); "image urls extracted");
+        return Ok(urls.iter().map(|url| url.to_string()).collect());
+    }
+
+    /// The image servers answer to requests with an empty referer.
+    fn image_headers(&self, _url: &str) -> Vec<(String, String)> {
+        return vec![("Referer".to_string(), String::new())];
+    }
+}
+
+fn oneshot_provider(frag: ElementRef) -> Result<Provider, SerieParseError> {
+    // Extract scan info.
+    let scan_selector = Selector::parse("span").expect("Selector is hardcoded.");
+    let Some(scan_node) = frag.select(&scan_selector).next() else {
+        return Err(SerieParseError::MissingScan);
+    };
+    let scan = match scan_node.text().next() {
+        Some(text) => text.trim().to_string(),
+        None => return Err(SerieParseError::MissingScan),
+    };
+
+    // Extract view url.
+    let a_selector = Selector::parse("a").expect("Selector is hardcoded.");
+    let Some(a_node) = frag.select(&a_selector).next() else {
+        return Err(SerieParseError::MissingScan);
+    };
+    let link = match a_node.attr("href") {
+        Some(text) => text.to_string(),
+        None => return Err(SerieParseError::MissingScan),
+    };
+
+    return Ok(Provider { scan, link });
+}
+
+fn serie_provider(frag: ElementRef) -> Result<Provider, SerieParseError> {
+    let a_selector = Selector::parse("a").expect("Selector is hardcoded.");
+
+    // Extract scan info.
+    let Some(scan_node) = frag.select(&a_selector).next() else {
+        return Err(SerieParseError::MissingScan);
+    };
+    let scan = match scan_node.text().next() {
+        Some(text) => text.trim().to_string(),
+        None => return Err(SerieParseError::MissingScan),
+    };
+
+    // Extract view url.
+    let Some(link_node) = frag.select(&a_selector).last() else {
+        return Err(SerieParseError::MissingScan);
+    };
+    let link = match link_node.attr("href") {
+        Some(text) => text.to_string(),
+        None => return Err(SerieParseError::MissingScan),
+    };
+
+    return Ok(Provider { scan, link });
+}
+
+fn paginated(html: &str) -> Result<Vec<Url>, DownloadError> {
+    // First part of the url
+    let search_string = "var dirPath = '";
+    let start_index = html
+        .find(search_string)
+        .ok_or(DownloadError::MissingImages)?;
+    let after_start = start_index + search_string.len();
+    let end_index = html[after_start..]
+        .find("'")
+        .ok_or(DownloadError::MissingImages)?;
+    let full_end_index = after_start + end_index;
+    let extract_url = &html[after_start..full_end_index];
+
+    // Second extract ulist
+    let start_delimiter = "JSON.parse('[";
+    let end_delimiter = "]');";
+
+    let start_ix = html
+        .find(start_delimiter)
+        .ok_or(DownloadError::MissingImages)?;
+    let after_start_2 = start_ix + start_delimiter.len();
+    let end_ix = html[after_start_2..]
+        .find(end_delimiter)
+        .ok_or(DownloadError::MissingImages)?;
+    let text_list = &html[after_start_2..after_start_2 + end_ix].replace("\"", "");
+    let vec_names = text_list.split(",");
+
+    return vec_names
+        .map(|nombre| format!("{}{}", extract_url, nombre))
+        .map(|url_tex| Url::parse(&url_tex).map_err(|_| DownloadError::InvalidImageUrl(url_tex)))
+        .collect();
+}
+
+fn cascade(html: &str) -> Result<Vec<Url>, DownloadError> {
+    let document = Html::parse_document(html);
+
+    let image_selector = Selector::parse("img.viewer-img").expect("Selector is hardcoded.");
+    let images = document.select(&image_selector);
+    let mut urls: Vec<Url> = Vec::new();
+
+    for image in images {
+        let url_text = image.attr("data-src").ok_or(DownloadError::MissingImages)?;
+        let url_obj = Url::parse(url_text.trim())
+            .map_err(|_| DownloadError::InvalidImageUrl(url_text.to_string()))?;
+
+        urls.push(url_obj);
+    }
+
+    if urls.is_empty() {
+        return Err(DownloadError::MissingImages);
+    }
+    return Ok(urls);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEARCH_HTML: &str = include_str!("fixtures/tmo_search.html");
+
+    #[test]
+    fn search_url_encodes_the_query() {
+        let url = Tmo::default().search_url("one piece & co").unwrap();
+        assert_eq!(
+            url,
+            "https://zonatmo.com/library?_pg=1&title=one+piece+%26+co"
+        );
+    }
+
+    #[test]
+    fn search_url_follows_the_base_domain() {
+        let tmo = Tmo::default().with_base_domain("example.com");
+        assert!(
+            tmo.search_url("x")
+                .unwrap()
+                .starts_with("https://example.com/library?")
+        );
+    }
+
+    #[test]
+    fn parse_search_reads_every_serie() {
+        let results = Tmo::default().parse_search(SEARCH_HTML);
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(results[0].title, "One Piece");
+        assert_eq!(results[0].kind, "manga");
+        assert_eq!(results[0].index, 12345);
+        assert_eq!(
+            results[0].url,
+            "https://zonatmo.com/library/manga/12345/one-piece"
+        );
+        assert_eq!(results[0].score, Some(9.47));
+    }
+
+    #[test]
+    fn parse_search_resolves_relative_links() {
+        let results = Tmo::default().parse_search(SEARCH_HTML);
+        assert_eq!(results[1].title, "Solo Leveling");
+        assert_eq!(results[1].kind, "manhwa");
+        assert_eq!(results[1].index, 67890);
+        assert_eq!(
+            results[1].url,
+            "https://zonatmo.com/library/manhwa/67890/solo-leveling"
+        );
+        assert_eq!(results[1].score, None);
+    }
+
+    #[test]
+    fn parse_chapter_reads_paginated_reader() {
+        let html = "var dirPath = 'https://img.example.com/abc/'; \
+                    var images = JSON.parse('[\"1.webp\",\"2.webp\"]');";
+        let urls = Tmo::default().parse_chapter(html).unwrap();
+        assert_eq!(
+            urls,
+            vec![
+                "https://img.example.com/abc/1.webp",
+                "https://img.example.com/abc/2.webp"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_chapter_without_images_is_an_error() {
+        let truncated = "var dirPath = 'https://img.example.com/abc/";
+        assert!(matches!(
+            Tmo::default().parse_chapter(truncated),
+            Err(DownloadError::MissingImages)
+        ));
+        let cascade = "<img class='viewer-img' src='1.webp'>";
+        assert!(matches!(
+            Tmo::default().parse_chapter(cascade),
+            Err(DownloadError::MissingImages)
+        ));
+    }
+
+    fn parse(tmo: &Tmo, url: &str) -> Result<SerieUrlInfo, UrlError> {
+        return tmo.parse_url(&Url::parse(url).unwrap());
+    }
+
+    #[test]
+    fn parse_url_extracts_the_serie() {
+        let url_info = parse(
+            &Tmo::default(),
+            "https://zonatmo.com/library/one_shot/42/slug",
+        )
+        .unwrap();
+        assert_eq!(url_info.index, 42);
+        assert_eq!(url_info.kind, "one_shot");
+        assert_eq!(url_info.slug, "slug");
+        assert!(url_info.is_oneshot);
+    }
+
+    #[test]
+    fn parse_url_makes_the_url_canonical() {
+        let url_info = parse(
+            &Tmo::default(),
+            "http://WWW.LectorTMO.com.:8080/library/manga/42/slug?page=2#top",
+        )
+        .unwrap();
+        assert_eq!(url_info.url, "https://zonatmo.com/library/manga/42/slug");
+    }
+
+    #[test]
+    fn parse_url_rejects_other_sites_and_paths() {
+        let tmo = Tmo::default();
+        assert!(matches!(
+            parse(&tmo, "https://example.com/library/manga/42/slug"),
+            Err(UrlError::UnknownHost)
+        ));
+        assert!(matches!(
+            parse(&tmo, "https://zonatmo.com/groups/42/slug"),
+            Err(UrlError::InvalidFormat)
+        ));
+        assert!(matches!(
+            parse(&tmo, "https://zonatmo.com/library/manga/abc/slug"),
+            Err(UrlError::InvalidId)
+        ));
+    }
+
+    #[test]
+    fn mirrors_are_recognized_without_www_case_or_trailing_dot() {
+        let tmo = Tmo::default().with_mirror("WWW.Example.org.");
+        for host in [
+            "zonatmo.com",
+            "www.visortmo.com",
+            "LECTORTMO.COM",
+            "example.org.",
+        ] {
+            let url = Url::parse(&format!("https://{}/", host)).unwrap();
+            assert!(tmo.recognizes(&url), "{} not recognized", host);
+        }
+        assert!(!tmo.recognizes(&Url::parse("https://example.com/").unwrap()));
+    }
+
+    #[test]
+    fn base_domain_keeps_the_previous_one_as_mirror() {
+        let tmo = Tmo::default().with_base_domain("www.New.example");
+        assert_eq!(tmo.base_domain(), "new.example");
+
+        let url_info = parse(&tmo, "https://zonatmo.com/library/manga/42/slug").unwrap();
+        assert_eq!(url_info.url, "https://new.example/library/manga/42/slug");
+    }
+
+    #[test]
+    fn reader_url_is_canonical() {
+        let tmo = Tmo::default();
+        let url = Url::parse("http://visortmo.com:8080/viewer/abc/cascade#page").unwrap();
+        assert_eq!(
+            tmo.reader_url(&url).unwrap().as_str(),
+            "https://zonatmo.com/viewer/abc/cascade"
+        );
+        let serie = Url::parse("https://zonatmo.com/library/manga/42/slug").unwrap();
+        assert!(tmo.reader_url(&serie).is_none());
+    }
+    #[test]
+    fn parse_reader_resolves_relative_links_and_reads_the_scan() {
+        let html = r#"<html><body>
+            <h1>Serie</h1>
+            <h4>Capítulo 12.50</h4>
+            <a href="/library/manga/42/slug">Serie</a>
+            <a href="/groups/7/scan-name"> Scan Name </a>
+        </body></html>"#;
+        let url = Url::parse("https://zonatmo.com/viewer/abc/paginated").unwrap();
+        let reader = Tmo::default().parse_reader(&url, html);
+        assert_eq!(
+            reader.serie_url.as_deref(),
+            Some("https://zonatmo.com/library/manga/42/slug")
+        );
+        assert_eq!(reader.chapter_name.as_deref(), Some("Capítulo 12.50"));
+        assert_eq!(reader.chapter_number, Some((12, 50)));
+        assert_eq!(reader.scan.as_deref(), Some("Scan Name"));
+    }
+
+    #[test]
+    fn same_reader_compares_the_upload() {
+        let tmo = Tmo::default();
+        let paginated = Url::parse("https://zonatmo.com/viewer/abc/paginated").unwrap();
+        let cascade = Url::parse("http://visortmo.com/viewer/abc/cascade").unwrap();
+        let other = Url::parse("https://zonatmo.com/viewer/def/paginated").unwrap();
+        assert!(tmo.same_reader(&paginated, &cascade));
+        assert!(!tmo.same_reader(&paginated, &other));
+    }
+}